@@ -0,0 +1,73 @@
+//! Benchmarks [`parse_into_arena`] against parsing into a `Vmf<String>`,
+//! and prints how many allocations each makes.
+//!
+//! Set `VMF_CORPUS` to a directory of `.vmf` files, otherwise a synthetic file is generated.
+//! Run with `cargo bench --bench arena --features arena`.
+
+mod common;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use vmf_parser_nom::{arena::parse_into_arena, ast::Vmf, parse};
+
+/// Counts allocations, including reallocations.
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+/// Number of allocations made by `f`.
+fn count_allocations<T>(f: impl FnOnce() -> T) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    black_box(f());
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+fn bench_arena(c: &mut Criterion) {
+    let mut group = c.benchmark_group("arena");
+    for (name, input) in common::corpus() {
+        if parse::<&str, ()>(&input).is_err() {
+            eprintln!("{name}: failed to parse, skipping");
+            continue;
+        }
+
+        let owned = count_allocations(|| parse::<String, ()>(&input));
+        let mut arena = String::new();
+        let arena_allocations = count_allocations(|| parse_into_arena::<()>(&input, &mut arena));
+        println!("{name}: {owned} allocations as Vmf<String>, {arena_allocations} with an arena");
+
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_with_input(BenchmarkId::new("String", &name), &input, |b, input| {
+            b.iter(|| -> Vmf<String> { parse::<String, ()>(black_box(input)).unwrap() })
+        });
+        group.bench_with_input(BenchmarkId::new("arena", &name), &input, |b, input| {
+            let mut arena = String::new();
+            b.iter(|| {
+                parse_into_arena::<()>(black_box(input), &mut arena).map(|vmf| vmf.blocks.len())
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_arena);
+criterion_main!(benches);
@@ -0,0 +1,54 @@
+//! Inputs shared by the benchmarks.
+
+use std::{env, fs};
+
+/// Loads the files in `VMF_CORPUS`, or a synthetic file if it isn't set.
+pub fn corpus() -> Vec<(String, String)> {
+    match env::var_os("VMF_CORPUS") {
+        Some(dir) => load_corpus(&dir),
+        None => {
+            eprintln!("VMF_CORPUS not set, using a synthetic file");
+            vec![("synthetic.vmf".to_string(), synthetic_vmf(2000))]
+        }
+    }
+}
+
+/// Loads every `.vmf` file in `dir`.
+fn load_corpus(dir: &std::ffi::OsStr) -> Vec<(String, String)> {
+    let mut corpus = Vec::new();
+    for entry in fs::read_dir(dir).expect("failed to read VMF_CORPUS") {
+        let path = entry.expect("failed to read VMF_CORPUS").path();
+        if !path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("vmf")) {
+            continue;
+        }
+        match fs::read_to_string(&path) {
+            Ok(input) => corpus.push((path.display().to_string(), input)),
+            Err(e) => eprintln!("{}: {e}, skipping", path.display()),
+        }
+    }
+    corpus
+}
+
+/// Generates a map with a world of `solids` cubes and an entity for every 10 solids.
+fn synthetic_vmf(solids: usize) -> String {
+    let mut vmf = String::from(
+        "versioninfo\n{\n\t\"editorversion\" \"400\"\n\t\"mapversion\" \"1\"\n}\nworld\n{\n\t\"id\" \"1\"\n\t\"classname\" \"worldspawn\"\n",
+    );
+    for i in 0..solids {
+        vmf.push_str(&format!("\tsolid\n\t{{\n\t\t\"id\" \"{i}\"\n"));
+        for side in 0..6 {
+            vmf.push_str(&format!(
+                "\t\tside\n\t\t{{\n\t\t\t\"id\" \"{}\"\n\t\t\t\"plane\" \"({i} 0 0) ({i} 64 0) ({i} 64 64)\"\n\t\t\t\"material\" \"DEV/DEV_MEASUREWALL01A\"\n\t\t\t\"uaxis\" \"[1 0 0 0] 0.25\"\n\t\t\t\"vaxis\" \"[0 -1 0 0] 0.25\"\n\t\t\t\"rotation\" \"0\"\n\t\t\t\"lightmapscale\" \"16\"\n\t\t\t\"smoothing_groups\" \"0\"\n\t\t}}\n",
+                i * 6 + side
+            ));
+        }
+        vmf.push_str("\t}\n");
+    }
+    vmf.push_str("}\n");
+    for i in 0..solids / 10 {
+        vmf.push_str(&format!(
+            "entity\n{{\n\t\"id\" \"{i}\"\n\t\"classname\" \"light\"\n\t\"_light\" \"255 255 255 200\"\n\t\"origin\" \"{i} 0 64\"\n}}\n"
+        ));
+    }
+    vmf
+}
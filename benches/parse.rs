@@ -0,0 +1,28 @@
+//! Benchmarks [`parse`] over a corpus of vmf files, reporting MB/s.
+//!
+//! Set `VMF_CORPUS` to a directory of `.vmf` files, otherwise a synthetic file is generated.
+//! Run with `cargo bench --bench parse`.
+
+mod common;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use vmf_parser_nom::parse;
+
+fn bench_parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for (name, input) in common::corpus() {
+        if parse::<&str, ()>(&input).is_err() {
+            eprintln!("{name}: failed to parse, skipping");
+            continue;
+        }
+
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(&name), &input, |b, input| {
+            b.iter(|| parse::<&str, ()>(black_box(input)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_parse);
+criterion_main!(benches);
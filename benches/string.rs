@@ -0,0 +1,45 @@
+//! Compares [`string`] against the escape aware [`string_escaped`], so escaping support
+//! doesn't slow down the common case of strings without any escapes.
+//!
+//! Run with `cargo bench --bench string`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use vmf_parser_nom::parsers::{string, string_escaped};
+
+/// Typical values from a vmf, none of them escaped.
+const PLAIN: [&str; 4] = [
+    r#""DEV/DEV_MEASUREWALL01A""#,
+    r#""(0 0 0) (0 64 0) (0 64 64)""#,
+    r#""[1 0 0 0] 0.25""#,
+    r#""""#,
+];
+
+/// Values that need escapes to parse correctly.
+const ESCAPED: [&str; 3] =
+    [r#""say \"hi\"""#, r#""materials\\dev\\floor""#, r#""a \"quoted\" path\\to\\file""#];
+
+fn bench_strings(c: &mut Criterion) {
+    let mut group = c.benchmark_group("string");
+    for (name, inputs) in [("plain", &PLAIN[..]), ("escaped", &ESCAPED[..])] {
+        group.throughput(Throughput::Bytes(inputs.iter().map(|s| s.len() as u64).sum()));
+        // `string` stops at the first quote, so it is only correct for the plain inputs
+        group.bench_with_input(BenchmarkId::new("take_until", name), inputs, |b, inputs| {
+            b.iter(|| {
+                for input in inputs {
+                    let _ = black_box(string::<()>(black_box(input)));
+                }
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("escape_aware", name), inputs, |b, inputs| {
+            b.iter(|| {
+                for input in inputs {
+                    let _ = black_box(string_escaped::<()>(black_box(input)));
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_strings);
+criterion_main!(benches);
@@ -0,0 +1,140 @@
+//! Parse into a single string buffer instead of allocating a `String` for every name, key, and value.
+
+use crate::ast::{Block, Property, Vmf};
+use crate::owned::parsers::nom_prelude::{ContextError, ParseError};
+
+/// Parse a `&str` into a [`Vmf`] like [`parse()`](crate::parse), copying every string
+/// into `arena` so the output does not borrow from `input`.
+///
+/// Makes one allocation for all the strings, compared to two for each property
+/// with `Vmf<String>`, and builds the tree only once. Any existing contents of `arena` are cleared.
+///
+/// # Examples
+///
+/// ```rust
+/// use vmf_parser_nom::arena::parse_into_arena;
+///
+/// let mut arena = String::new();
+/// let vmf = {
+///     let input = String::from("block{\"key\" \"value\"}");
+///     parse_into_arena::<()>(&input, &mut arena).unwrap()
+/// };
+/// assert_eq!(vmf.blocks[0].props[0].value, "value");
+/// ```
+pub fn parse_into_arena<'i, 'arena, E>(
+    input: &'i str,
+    arena: &'arena mut String,
+) -> Result<Vmf<&'arena str>, E>
+where
+    E: ParseError<&'i str> + ContextError<&'i str>,
+{
+    // parse once into spans of `input`, then repoint them at the arena in place
+    let mut vmf = crate::parse::<Span, E>(input)?;
+
+    arena.clear();
+    arena.reserve(total_bytes(&vmf));
+    // the root name is not part of `input`
+    vmf.inner.name = push(arena, Vmf::<Span>::ROOT_NAME);
+    let base = input.as_ptr() as usize;
+    for block in vmf.inner.blocks.iter_mut() {
+        copy_block(block, input, base, arena);
+    }
+
+    let arena: &'arena str = arena;
+    Ok(Vmf { inner: slice_block(vmf.inner, arena) })
+}
+
+/// Where a string is. Parsed as the address of a slice of the input,
+/// then changed to an offset into the arena by [`copy_block`].
+///
+/// Same layout as `&str`, so [`slice_block`] reuses every `Vec` instead of allocating.
+#[derive(Debug, Clone, Copy)]
+struct Span {
+    start: usize,
+    len: usize,
+}
+
+impl From<&str> for Span {
+    fn from(s: &str) -> Self {
+        Self { start: s.as_ptr() as usize, len: s.len() }
+    }
+}
+
+fn total_bytes(block: &Block<Span>) -> usize {
+    block.name.len
+        + block.props.iter().map(|prop| prop.key.len + prop.value.len).sum::<usize>()
+        + block.blocks.iter().map(total_bytes).sum::<usize>()
+}
+
+/// Push a string into the arena, returning where it is.
+fn push(arena: &mut String, s: &str) -> Span {
+    let start = arena.len();
+    arena.push_str(s);
+    Span { start, len: s.len() }
+}
+
+/// Copy a span of `input` into the arena, repointing it at the copy.
+fn copy_span(span: &mut Span, input: &str, base: usize, arena: &mut String) {
+    let start = span.start - base;
+    *span = push(arena, &input[start..start + span.len]);
+}
+
+fn copy_block(block: &mut Block<Span>, input: &str, base: usize, arena: &mut String) {
+    copy_span(&mut block.name, input, base, arena);
+    for prop in block.props.iter_mut() {
+        copy_span(&mut prop.key, input, base, arena);
+        copy_span(&mut prop.value, input, base, arena);
+    }
+    for block in block.blocks.iter_mut() {
+        copy_block(block, input, base, arena);
+    }
+}
+
+fn slice_block(block: Block<Span>, arena: &str) -> Block<&str> {
+    let slice = |span: Span| &arena[span.start..span.start + span.len];
+    Block {
+        name: slice(block.name),
+        props: block
+            .props
+            .into_iter()
+            .map(|prop| Property { key: slice(prop.key), value: slice(prop.value) })
+            .collect(),
+        blocks: block.blocks.into_iter().map(|block| slice_block(block, arena)).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arena_equivalence() {
+        let input = "world
+{
+\t\"id\" \"1\"
+\t\"skyname\" \"sky_day01_01\"
+\tsolid
+\t{
+\t\tside
+\t\t{
+\t\t\t\"material\" \"DEV/DEV_MEASUREWALL01A\"
+\t\t}
+\t}
+}
+entity
+{
+\t\"classname\" \"info_player_start\"
+}";
+        let mut arena = String::from("old contents");
+        let output = parse_into_arena::<()>(input, &mut arena).unwrap();
+
+        assert_eq!(output, crate::parse::<&str, ()>(input).unwrap());
+        assert_eq!(output.to_string(), input);
+        // root name is copied too
+        assert!(arena.starts_with("root"));
+        assert!(!arena.contains("old contents"));
+
+        let mut arena = String::new();
+        assert!(parse_into_arena::<()>("world{", &mut arena).is_err());
+    }
+}
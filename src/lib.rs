@@ -89,13 +89,22 @@ pub mod error {
     pub use nom::error::VerboseError;
 }
 
-mod nom_helpers;
+#[cfg(feature = "arena")]
+pub mod arena;
+pub mod nom_helpers;
+pub mod preprocess;
+
 mod owned;
 
+use nom_helpers::{ParseErrorExt, ParseErrorReport};
 use owned::ast::*;
 use owned::parsers::nom_prelude::*;
-use owned::parsers::vmf;
+use owned::parsers::ParseConfig;
 pub use owned::*;
+use std::collections::HashMap;
+use std::io::{self, Read};
+use std::ops::Range;
+use std::path::Path;
 
 // pub(crate) type VerboseError<I> = VerboseError<I>;
 
@@ -138,6 +147,76 @@ macro_rules! traverse {
     };
 }
 
+/// Macro for concisely constructing a [`Vmf`]. Takes any number of blocks in the same form as [`block!`].
+///
+/// # Examples
+///
+/// ```rust
+/// use vmf_parser_nom::{parse, vmf};
+/// use vmf_parser_nom::ast::Vmf;
+///
+/// let vmf: Vmf<&str> = vmf! {
+///     world {
+///         "id" "1";
+///         solid { "id" "2"; }
+///     }
+///     entity { "classname" "light"; }
+/// };
+/// let input = r#"world { "id" "1" solid { "id" "2" } } entity { "classname" "light" }"#;
+/// assert_eq!(vmf, parse::<&str, ()>(input).unwrap());
+/// ```
+#[macro_export]
+macro_rules! vmf {
+    ($($name:ident { $($body:tt)* })*) => {
+        $crate::ast::Vmf::new(vec![$($crate::block!($name { $($body)* })),*])
+    };
+}
+
+/// Macro for concisely constructing a [`Block`].
+/// Usage is `block!(name { "key" "value"; child { ... } })`.
+/// Properties are a literal key and any expression for the value, ending with a `;`.
+///
+/// # Examples
+///
+/// ```rust
+/// use vmf_parser_nom::block;
+/// use vmf_parser_nom::ast::{Block, Property};
+///
+/// let brightness = "255 255 255 200";
+/// let block: Block<String> = block!(entity {
+///     "classname" "light";
+///     "_light" brightness;
+///     editor { "visgroupshown" "1"; }
+/// });
+/// assert_eq!(block.props[1], Property::new("_light", "255 255 255 200"));
+/// assert_eq!(block.blocks[0].name, "editor");
+/// ```
+#[macro_export]
+macro_rules! block {
+    ($name:ident { $($body:tt)* }) => {{
+        // not mutated if the body is empty
+        #[allow(unused_mut)]
+        let mut block = $crate::ast::Block::new(stringify!($name), vec![], vec![]);
+        $crate::__block_body!(block; $($body)*);
+        block
+    }};
+}
+
+/// Pushes the properties and blocks of the body of a [`block!`] one at a time.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __block_body {
+    ($block:ident;) => {};
+    ($block:ident; $key:literal $value:expr; $($rest:tt)*) => {
+        $block.props.push($crate::ast::Property::new($key, $value));
+        $crate::__block_body!($block; $($rest)*);
+    };
+    ($block:ident; $name:ident { $($body:tt)* } $($rest:tt)*) => {
+        $block.blocks.push($crate::block!($name { $($body)* }));
+        $crate::__block_body!($block; $($rest)*);
+    };
+}
+
 // FromStr unable to be implemented because dumb lifetime stuff
 /// Parse a `&str` into a [`Vmf`], completely ignoring whitespace.
 /// You can specify the output string type to be
@@ -147,22 +226,528 @@ macro_rules! traverse {
 /// `()`, [`(I, nom::error::ErrorKind)`](nom::error::ErrorKind), [`nom::error::Error<&str>`], [`nom::error::VerboseError<&str>`].
 /// Or other types that impl [`ParseError`] and [`ContextError`]
 ///
+/// Errors immediately with "not a VMF file" if the input does not start with a
+/// block name or comment, after whitespace.
+///
 /// See [Vmf Format](./index.html#vmf-format).
+///
+/// # Custom string types
+///
+/// Any type that implements `From<&str>` works, such as an interned string.
+/// Implement [`Display`](std::fmt::Display) to display the output, and
+/// [`AsRef<str>`] for most of the methods on [`Block`].
+///
+/// ```rust
+/// use std::cell::RefCell;
+/// use std::collections::HashSet;
+/// use std::fmt;
+/// use std::rc::Rc;
+/// use vmf_parser_nom::parse;
+///
+/// /// A string that shares its allocation with every equal string.
+/// #[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+/// struct InternedStr(Rc<str>);
+///
+/// thread_local! {
+///     static STRINGS: RefCell<HashSet<Rc<str>>> = RefCell::new(HashSet::new());
+/// }
+///
+/// impl From<&str> for InternedStr {
+///     fn from(s: &str) -> Self {
+///         STRINGS.with(|strings| {
+///             let mut strings = strings.borrow_mut();
+///             if let Some(interned) = strings.get(s) {
+///                 return Self(interned.clone());
+///             }
+///             let interned: Rc<str> = s.into();
+///             strings.insert(interned.clone());
+///             Self(interned)
+///         })
+///     }
+/// }
+///
+/// impl AsRef<str> for InternedStr {
+///     fn as_ref(&self) -> &str {
+///         &self.0
+///     }
+/// }
+///
+/// impl fmt::Display for InternedStr {
+///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         f.write_str(&self.0)
+///     }
+/// }
+///
+/// let input = "solid\n{\n\t\"id\" \"1\"\n}\nsolid\n{\n\t\"id\" \"1\"\n}";
+/// let vmf = parse::<InternedStr, ()>(input).unwrap();
+/// assert_eq!(vmf.to_string(), input);
+/// assert_eq!(vmf.solids().count(), 2);
+/// // both values share one allocation
+/// assert!(Rc::ptr_eq(&vmf.blocks[0].props[0].value.0, &vmf.blocks[1].props[0].value.0));
+/// ```
 pub fn parse<'a, O, E>(input: &'a str) -> Result<Vmf<O>, E>
 where
     O: From<&'a str>,
     E: ParseError<&'a str> + ContextError<&'a str>,
 {
-    match vmf(input) {
-        Ok((_, vmf)) => Ok(vmf),
-        Err(e) => match e {
-            nom::Err::Incomplete(_) => Err(ContextError::add_context(
-                input,
-                "incomplete",
-                ParseError::from_error_kind(input, ErrorKind::Fail),
-            )),
-            nom::Err::Error(e) => Err(e),
-            nom::Err::Failure(e) => Err(e),
-        },
+    parse_with_config(input, &ParseConfig::default())
+}
+
+/// Parse a `&str` into a [`Vmf`] like [`parse()`], using the options in `config`.
+///
+/// Options that rewrite the input are not applied here, as the output could not
+/// borrow from it. Call [`ParseConfig::preprocess`] first and parse the result,
+/// or use [`parse_with_config_owned()`] to apply them all.
+///
+/// # Examples
+///
+/// ```rust
+/// use vmf_parser_nom::parse_with_config;
+/// use vmf_parser_nom::parsers::ParseConfig;
+///
+/// let config = ParseConfig::default().with_quote_char('\'').with_join_line_continuations(true);
+/// let input = config.preprocess("block{'key' 'a long \\\nvalue'}");
+/// let vmf = parse_with_config::<&str, ()>(&input, &config).unwrap();
+/// assert_eq!(vmf.blocks[0].props[0].value, "a long value");
+/// ```
+pub fn parse_with_config<'a, O, E>(input: &'a str, config: &ParseConfig) -> Result<Vmf<O>, E>
+where
+    O: From<&'a str>,
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    let input = match input.strip_prefix('\u{feff}') {
+        Some(rest) if config.strip_bom => rest,
+        _ => input,
+    };
+
+    // cheap check so binary files fail immediately
+    let start = input.trim_start();
+    if let Some(c) = start.chars().next() {
+        let root_prop = config.root_properties && c == config.quote_char;
+        if !((config.ident_pred)(c) || c == '/' || root_prop) {
+            return Err(E::from_context(start, "not a VMF file"));
+        }
     }
+
+    let mut block = config.block::<O, E>();
+    let parsed = if config.root_properties {
+        owned::parsers::root_items(input, config)
+    } else {
+        map(many1(&mut block), Vmf::new)(input)
+    };
+    let (rest, vmf) = match parsed {
+        Ok(parsed) => parsed,
+        Err(nom::Err::Incomplete(_)) => return Err(E::from_context(input, "incomplete")),
+        Err(nom::Err::Error(e) | nom::Err::Failure(e)) => return Err(e),
+    };
+
+    if config.require_complete {
+        let rest =
+            many0_count(owned::parsers::ignorable::<()>)(rest).map_or(rest, |(rest, _)| rest);
+        if !rest.is_empty() {
+            return match block(rest) {
+                Err(nom::Err::Error(e) | nom::Err::Failure(e)) => Err(e),
+                // a block that failed inside `many1` will fail again
+                _ => Err(E::from_context(rest, "expected block")),
+            };
+        }
+    }
+
+    Ok(vmf)
+}
+
+/// Parse a `&str` into an owned [`Vmf`], applying every option in `config`,
+/// including those that rewrite the input or the parsed strings, see
+/// [`ParseConfig::preprocess`] and [`ParseConfig::postprocess`].
+/// Errors are converted into an owned [`ParseErrorReport`], with locations in the rewritten input.
+///
+/// # Examples
+///
+/// ```rust
+/// use vmf_parser_nom::parse_with_config_owned;
+/// use vmf_parser_nom::parsers::ParseConfig;
+///
+/// let config = ParseConfig::default().with_lowercase_names(true);
+/// let vmf = parse_with_config_owned::<String>("SOLID{}", &config).unwrap();
+/// assert_eq!(vmf.blocks[0].name, "solid");
+/// ```
+pub fn parse_with_config_owned<O>(
+    input: &str,
+    config: &ParseConfig,
+) -> Result<Vmf<O>, ParseErrorReport>
+where
+    O: for<'b> From<&'b str>,
+{
+    let input = config.preprocess(input);
+    let vmf = parse_with_config::<&str, VerboseError<&str>>(&input, config)
+        .map_err(|e| ParseErrorReport::from_verbose(&input, &e))?;
+    let vmf = config.postprocess(vmf);
+    Ok(Vmf { inner: vmf.inner.map_strings(&mut |s| O::from(&s)) })
+}
+
+/// Parse a `&str` into a [`Vmf`] borrowing from `input`, like [`parse()`] without any
+/// type parameters. Errors are converted into an owned [`ParseErrorReport`].
+///
+/// # Examples
+///
+/// ```rust
+/// use vmf_parser_nom::parse_borrowed;
+///
+/// let vmf = parse_borrowed("world { \"id\" \"1\" }").unwrap();
+/// assert_eq!(vmf.blocks[0].props[0].value, "1");
+///
+/// let err = parse_borrowed("world {\n\t\"id\"\n}").unwrap_err();
+/// assert_eq!((err.line, err.column), (2, 2));
+/// ```
+pub fn parse_borrowed(input: &str) -> Result<Vmf<&str>, ParseErrorReport> {
+    parse::<&str, VerboseError<&str>>(input).map_err(|e| ParseErrorReport::from_verbose(input, &e))
+}
+
+/// Parse a `&str` into a [`Vmf`] like [`parse()`], with properties quoted by `quote`
+/// instead of `"`. For formats related to vmf that use single quotes.
+/// See [`DisplayOptions::quote_char`] for the opposite.
+pub fn parse_with_quote<'a, O, E>(input: &'a str, quote: char) -> Result<Vmf<O>, E>
+where
+    O: From<&'a str>,
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    parse_with_config(input, &ParseConfig::default().with_quote_char(quote))
+}
+
+/// Parse a `&str` into a [`Vmf`] like [`parse()`], recording the byte range
+/// of every name, key, and value in the input. See [`Spanned`].
+/// Use [`Vmf::strip_spans`] to get the plain ast.
+pub fn parse_spanned<'a, E>(input: &'a str) -> Result<Vmf<Spanned<&'a str>>, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    let vmf = parse::<&str, E>(input)?;
+    Ok(Vmf { inner: vmf.inner.into_spanned(input) })
+}
+
+/// Parse a `&str` into a [`Vmf`] like [`parse()`], also recording whether each block
+/// has its `{` on the same line as its name. The styles are in depth first order with
+/// parents before children, not including the root, the same order as [`Block::walk`].
+/// For linters and formatters that preserve the original style.
+pub fn parse_with_brace_styles<'a, E>(input: &'a str) -> Result<(Vmf<&'a str>, Vec<BraceStyle>), E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    let vmf = parse::<&str, E>(input)?;
+    let styles = vmf.inner.brace_styles(input);
+    Ok((vmf, styles))
+}
+
+/// Parse a `&str` into an [`OrderedVmf`], like [`parse()`] but keeping properties
+/// and blocks in their original order. For exact round trips of hand written files
+/// that have properties after blocks.
+pub fn parse_ordered<'a, O, E>(input: &'a str) -> Result<OrderedVmf<O>, E>
+where
+    O: From<&'a str>,
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    match many1(owned::parsers::ordered_block)(input) {
+        Ok((_, blocks)) => Ok(OrderedVmf { blocks }),
+        Err(nom::Err::Incomplete(_)) => Err(E::from_context(input, "incomplete")),
+        Err(nom::Err::Error(e) | nom::Err::Failure(e)) => Err(e),
+    }
+}
+
+/// Parse a `&str` into a [`Vmf`] like [`parse()`], also returning how many bytes were consumed.
+/// Whitespace after the last block is consumed, trailing comments and invalid input are not.
+pub fn parse_counted<'a, O, E>(input: &'a str) -> Result<(Vmf<O>, usize), E>
+where
+    O: From<&'a str>,
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    match owned::parsers::vmf(input) {
+        Ok((rest, vmf)) => Ok((vmf, input.len() - rest.len())),
+        Err(nom::Err::Incomplete(_)) => Err(E::from_context(input, "incomplete")),
+        Err(nom::Err::Error(e) | nom::Err::Failure(e)) => Err(e),
+    }
+}
+
+/// Parse a `&str` into an existing [`Vmf`] like [`parse()`], clearing it first.
+/// Reuses the allocation of the root block list, handy when parsing many files in a loop.
+///
+/// On error, `out` contains the blocks parsed before the error.
+pub fn parse_into<'a, O, E>(input: &'a str, out: &mut Vmf<O>) -> Result<(), E>
+where
+    O: From<&'a str>,
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    out.inner.name = Vmf::<O>::ROOT_NAME.into();
+    out.inner.props.clear();
+    out.inner.blocks.clear();
+
+    // same as `many1` in `vmf`
+    let mut input = input;
+    loop {
+        match owned::parsers::block::<O, E>(input) {
+            Ok((rest, block)) => {
+                out.inner.blocks.push(block);
+                input = rest;
+            }
+            Err(nom::Err::Error(_)) if !out.inner.blocks.is_empty() => return Ok(()),
+            Err(nom::Err::Incomplete(_)) => return Err(E::from_context(input, "incomplete")),
+            Err(nom::Err::Error(e) | nom::Err::Failure(e)) => return Err(e),
+        }
+    }
+}
+
+/// Parse a `&str` into a [`Vmf`] like [`parse()`], calling `intern` for every name,
+/// key, and value instead of using `From<&str>`.
+/// Lets maps with thousands of repeated strings like `classname` share them.
+pub fn parse_interned<'a, S, E, F>(input: &'a str, mut intern: F) -> Result<Vmf<S>, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+    F: FnMut(&'a str) -> S,
+{
+    let vmf = parse::<&str, E>(input)?;
+    Ok(Vmf { inner: vmf.inner.map_strings(&mut intern) })
+}
+
+/// Parse a `&str` into a [`Vmf`] like [`parse()`], passing every property value through `hook`.
+/// For substituting variables in values. Names and keys are left as is.
+/// Use [`Cow::Borrowed`](std::borrow::Cow::Borrowed) as the hook to change nothing.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::borrow::Cow;
+/// use vmf_parser_nom::parse_with_value_hook;
+///
+/// let input = r#"side { "material" "$basetexture" "plane" "(0 0 0) (1 0 0) (0 1 0)" }"#;
+/// let vmf = parse_with_value_hook::<()>(input, |value| {
+///     if value.contains("$basetexture") {
+///         Cow::Owned(value.replace("$basetexture", "DEV/DEV_MEASUREWALL01A"))
+///     } else {
+///         Cow::Borrowed(value)
+///     }
+/// })
+/// .unwrap();
+/// assert_eq!(vmf.blocks[0].props[0].value, "DEV/DEV_MEASUREWALL01A");
+/// assert!(matches!(vmf.blocks[0].props[1].value, Cow::Borrowed(_)));
+/// ```
+pub fn parse_with_value_hook<'a, E>(
+    input: &'a str,
+    mut hook: impl FnMut(&'a str) -> std::borrow::Cow<'a, str>,
+) -> Result<Vmf<std::borrow::Cow<'a, str>>, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    let vmf = parse::<&str, E>(input)?;
+    Ok(Vmf { inner: vmf.inner.map_values(&mut hook) })
+}
+
+/// Finds the byte range of every top level block by name without parsing them.
+/// Only scans for braces, skipping over quoted strings and comments, so it is much faster
+/// than [`parse()`] but doesn't check that the blocks are valid.
+/// Each range starts at the block name and ends after its closing brace, so it can be
+/// parsed on its own. A block missing its closing brace is left out.
+///
+/// # Examples
+///
+/// ```rust
+/// use vmf_parser_nom::index_top_level;
+///
+/// let input = "versioninfo\n{\n}\nworld\n{\n\t\"name\" \"}\"\n}\n";
+/// let index = index_top_level(input);
+/// let world = index["world"][0].clone();
+/// assert_eq!(&input[world], "world\n{\n\t\"name\" \"}\"\n}");
+/// ```
+pub fn index_top_level(input: &str) -> HashMap<String, Vec<Range<usize>>> {
+    let bytes = input.as_bytes();
+    let mut index: HashMap<String, Vec<Range<usize>>> = HashMap::new();
+    let mut depth = 0_usize;
+    // the last identifier at the top level, then the name of the current block
+    let mut name = 0..0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => {
+                let len = bytes[i + 1..].iter().position(|&c| c == b'"').unwrap_or(bytes.len());
+                i += len + 1;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                let len = bytes[i..].iter().position(|&c| c == b'\n').unwrap_or(bytes.len());
+                i += len;
+            }
+            b'{' => depth += 1,
+            b'}' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 && !name.is_empty() {
+                    let key = input[name.clone()].to_string();
+                    index.entry(key).or_default().push(name.start..i + 1);
+                    name = 0..0;
+                }
+            }
+            c if depth == 0 && owned::parsers::is_identifier_char(c as char) => {
+                let len = bytes[i..]
+                    .iter()
+                    .position(|&c| !owned::parsers::is_identifier_char(c as char))
+                    .unwrap_or(bytes.len() - i);
+                name = i..i + len;
+                i += len;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    index
+}
+
+/// Parse as many complete blocks as possible from a `&str` into a [`Vmf`], like [`parse()`].
+/// Returns the remaining input starting at the first incomplete or invalid block instead of erroring,
+/// so streaming consumers can append more data to it and parse again.
+pub fn parse_partial<'a, O, E>(input: &'a str) -> Result<(Vmf<O>, &'a str), E>
+where
+    O: From<&'a str>,
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    match many0(owned::parsers::block)(input) {
+        Ok((rest, blocks)) => Ok((Vmf::new(blocks), rest)),
+        Err(nom::Err::Incomplete(_)) => Err(E::from_context(input, "incomplete")),
+        Err(nom::Err::Error(e) | nom::Err::Failure(e)) => Err(e),
+    }
+}
+
+/// Parse as many blocks as possible from a `&str` into a [`Vmf`], like [`parse()`],
+/// also returning a report of the first error if the whole input could not be parsed.
+/// For debugging large files, the blocks before the error are kept.
+///
+/// Unlike [`parse()`], anything other than whitespace and comments after the last
+/// valid block is an error.
+pub fn parse_partial_ast(input: &str) -> (Vmf<String>, Option<ParseErrorReport>) {
+    let (vmf, rest) = match parse_partial::<&str, VerboseError<&str>>(input) {
+        Ok(parsed) => parsed,
+        Err(e) => return (Vmf::default(), Some(ParseErrorReport::from_verbose(input, &e))),
+    };
+    let vmf = vmf.into_owned();
+
+    let rest = many0_count(owned::parsers::ignorable::<()>)(rest).map_or(rest, |(rest, _)| rest);
+    if !rest.is_empty() {
+        let report = match owned::parsers::block::<&str, VerboseError<&str>>(rest) {
+            Err(nom::Err::Error(e) | nom::Err::Failure(e)) => {
+                ParseErrorReport::from_verbose(input, &e)
+            }
+            // a block that failed inside `many0` will fail again
+            _ => ParseErrorReport::new(input, rest, "expected block"),
+        };
+        (vmf, Some(report))
+    } else if vmf.blocks.is_empty() {
+        (vmf, Some(ParseErrorReport::new(input, rest, "expected block found EOF")))
+    } else {
+        (vmf, None)
+    }
+}
+
+/// Parse bytes into a [`Vmf`], like [`parse()`].
+/// Errors if the input is not valid UTF-8, the error points to the end of the
+/// valid part of the input.
+///
+/// See [`parse_bytes_lossy()`] for replacing invalid bytes instead.
+pub fn parse_bytes<'a, O, E>(input: &'a [u8]) -> Result<Vmf<O>, E>
+where
+    O: From<&'a str>,
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    match std::str::from_utf8(input) {
+        Ok(input) => parse(input),
+        Err(e) => {
+            // input up to `valid_up_to` is always valid
+            let valid = std::str::from_utf8(&input[..e.valid_up_to()]).unwrap_or_default();
+            let rest = &valid[valid.len()..];
+            Err(E::from_context(rest, "invalid utf-8"))
+        }
+    }
+}
+
+/// Parse bytes into a [`Vmf`], like [`parse()`].
+/// Invalid UTF-8 is replaced with [`U+FFFD REPLACEMENT CHARACTER`](std::char::REPLACEMENT_CHARACTER)
+/// using [`String::from_utf8_lossy`], useful for files with the occasional bad byte in a comment.
+///
+/// The input is decoded up front into a temporary, so the output string type and
+/// error type cannot borrow from it. Use `String` or similar and `()` for errors.
+pub fn parse_bytes_lossy<O, E>(input: &[u8]) -> Result<Vmf<O>, E>
+where
+    O: for<'b> From<&'b str>,
+    E: for<'b> ParseError<&'b str> + for<'b> ContextError<&'b str>,
+{
+    parse(&String::from_utf8_lossy(input))
+}
+
+/// Parse UTF-16 encoded bytes into a [`Vmf`], like [`parse()`].
+/// Detects the byte order from the BOM, defaulting to little endian if there is none.
+/// Errors if there is an odd number of bytes or invalid UTF-16.
+///
+/// Like [`parse_bytes_lossy()`], the input is transcoded into a temporary so the error
+/// type cannot borrow from it.
+pub fn parse_utf16<E>(input: &[u8]) -> Result<Vmf<String>, E>
+where
+    E: for<'b> ParseError<&'b str> + for<'b> ContextError<&'b str>,
+{
+    let (input, from_bytes): (_, fn([u8; 2]) -> u16) = match input {
+        [0xFF, 0xFE, rest @ ..] => (rest, u16::from_le_bytes),
+        [0xFE, 0xFF, rest @ ..] => (rest, u16::from_be_bytes),
+        _ => (input, u16::from_le_bytes),
+    };
+    if input.len() % 2 != 0 {
+        return Err(E::from_context("", "odd number of bytes in utf-16"));
+    }
+
+    let units = input.chunks_exact(2).map(|pair| from_bytes([pair[0], pair[1]]));
+    let string: String = match char::decode_utf16(units).collect() {
+        Ok(string) => string,
+        Err(_) => return Err(E::from_context("", "invalid utf-16")),
+    };
+    parse(&string)
+}
+
+/// Read everything from `reader` and parse it into a [`Vmf`], like [`parse()`].
+/// A convenience wrapper for when the input isn't already in memory.
+///
+/// The outer `Result` is the error from reading, including invalid UTF-8, and the inner one
+/// is the error from parsing. The input is read into a temporary, so the output string type
+/// cannot borrow from it and parse errors are converted into an owned [`ParseErrorReport`].
+///
+/// # Examples
+///
+/// ```rust
+/// use vmf_parser_nom::parse_reader;
+///
+/// let reader = "world{\"id\" \"1\"}".as_bytes();
+/// let vmf = parse_reader::<_, String>(reader).unwrap().unwrap();
+/// assert_eq!(vmf.blocks[0].name, "world");
+///
+/// let err = parse_reader::<_, String>("world{\n\"id\"}".as_bytes()).unwrap().unwrap_err();
+/// assert_eq!((err.line, err.column), (2, 1));
+/// ```
+pub fn parse_reader<R, O>(mut reader: R) -> io::Result<Result<Vmf<O>, ParseErrorReport>>
+where
+    R: Read,
+    O: for<'b> From<&'b str>,
+{
+    let mut input = String::new();
+    reader.read_to_string(&mut input)?;
+    Ok(parse_owned(&input))
+}
+
+/// Read the file at `path` and parse it into a [`Vmf`], like [`parse_reader()`].
+pub fn parse_file<P, O>(path: P) -> io::Result<Result<Vmf<O>, ParseErrorReport>>
+where
+    P: AsRef<Path>,
+    O: for<'b> From<&'b str>,
+{
+    let input = std::fs::read_to_string(path)?;
+    Ok(parse_owned(&input))
+}
+
+/// Parses into owned output, converting the error before `input` is dropped.
+fn parse_owned<O>(input: &str) -> Result<Vmf<O>, ParseErrorReport>
+where
+    O: for<'b> From<&'b str>,
+{
+    parse::<O, VerboseError<&str>>(input).map_err(|e| ParseErrorReport::from_verbose(input, &e))
 }
@@ -1,6 +1,6 @@
 //! Helper methods that should be in [`nom`] already.
 
-use nom::error::{ContextError, ErrorKind, ParseError};
+use nom::error::{ContextError, ErrorKind, ParseError, VerboseError, VerboseErrorKind};
 
 /// Helper methods that should be in [`nom`] already.
 pub trait NomErrExt<E> {
@@ -26,6 +26,9 @@ where
     /// Create [`ParseError`] from input and context.
     fn from_context(input: I, ctx: &'static str) -> Self;
 
+    /// Append context to an existing error, for building chains of context in custom parsers.
+    fn add_ctx(self, input: I, ctx: &'static str) -> Self;
+
     /// Wrap in [`Error`](nom::Err::Error) variant of [`nom::Err`].
     fn into_err(self) -> nom::Err<Self> {
         nom::Err::Error(self)
@@ -50,9 +53,176 @@ where
             ParseError::from_error_kind(input, ErrorKind::Fail),
         )
     }
+
+    fn add_ctx(self, input: I, ctx: &'static str) -> Self {
+        ContextError::add_context(input, ctx, self)
+    }
+}
+
+/// A parse error with an owned message and where in the input it happened,
+/// see [`parse_partial_ast()`](crate::parse_partial_ast).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseErrorReport {
+    /// Byte offset into the input.
+    pub offset: usize,
+    /// Line number, starting at 1.
+    pub line: usize,
+    /// Column in characters, starting at 1.
+    pub column: usize,
+    pub message: String,
+}
+
+impl ParseErrorReport {
+    /// Creates a report for an error at the start of `remaining`, which must be
+    /// the end of `input`.
+    pub fn new(input: &str, remaining: &str, message: impl Into<String>) -> Self {
+        let offset = input.len().saturating_sub(remaining.len());
+        let before = &input[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Self {
+            offset,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            message: message.into(),
+        }
+    }
+
+    /// Creates a report from the innermost error of a [`VerboseError`],
+    /// using its innermost context as the message if there is one.
+    pub fn from_verbose(input: &str, err: &VerboseError<&str>) -> Self {
+        let remaining = err.errors.first().map_or(input, |(remaining, _)| *remaining);
+        let message = err
+            .errors
+            .iter()
+            .find_map(|(_, kind)| match kind {
+                VerboseErrorKind::Context(ctx) => Some(ctx.to_string()),
+                _ => None,
+            })
+            .or_else(|| match err.errors.first() {
+                Some((_, VerboseErrorKind::Nom(kind))) => Some(kind.description().to_string()),
+                Some((_, VerboseErrorKind::Char(c))) => Some(format!("expected '{c}'")),
+                _ => None,
+            })
+            .unwrap_or_else(|| "unknown error".to_string());
+        Self::new(input, remaining, message)
+    }
+}
+
+impl std::fmt::Display for ParseErrorReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for ParseErrorReport {}
+
+/// Where an error happened, for any of the error types [`parse()`](crate::parse) supports.
+/// See [`explain_error`].
+pub trait ErrorLocation<I> {
+    /// Returns the remaining input at the innermost error and its [`ErrorKind`],
+    /// or `None` if the error type does not store them.
+    fn location(&self) -> Option<(I, ErrorKind)>;
+}
+
+impl<I> ErrorLocation<I> for () {
+    fn location(&self) -> Option<(I, ErrorKind)> {
+        None
+    }
+}
+
+impl<I: Clone> ErrorLocation<I> for (I, ErrorKind) {
+    fn location(&self) -> Option<(I, ErrorKind)> {
+        Some(self.clone())
+    }
+}
+
+impl<I: Clone> ErrorLocation<I> for nom::error::Error<I> {
+    fn location(&self) -> Option<(I, ErrorKind)> {
+        Some((self.input.clone(), self.code))
+    }
+}
+
+impl<I: Clone> ErrorLocation<I> for VerboseError<I> {
+    fn location(&self) -> Option<(I, ErrorKind)> {
+        // kind of the innermost nom error, at the innermost input
+        let (remaining, _) = self.errors.first()?;
+        let kind = self.errors.iter().find_map(|(_, kind)| match kind {
+            VerboseErrorKind::Nom(kind) => Some(*kind),
+            _ => None,
+        });
+        Some((remaining.clone(), kind.unwrap_or(ErrorKind::Fail)))
+    }
+}
+
+/// Describes an error of `kind` at the start of `remaining`, which must be the end of `input`,
+/// like `line 3, column 6: Tag`.
+pub fn explain(input: &str, remaining: &str, kind: ErrorKind) -> String {
+    ParseErrorReport::new(input, remaining, kind.description()).to_string()
+}
+
+/// Describes any error type the same way, see [`explain`].
+/// Errors without a location, like `()`, are described as `unknown location`.
+pub fn explain_error<'a, E: ErrorLocation<&'a str>>(input: &'a str, err: &E) -> String {
+    match err.location() {
+        Some((remaining, kind)) => explain(input, remaining, kind),
+        None => "parse error at unknown location".to_string(),
+    }
 }
 
 // create directly for verbose error without ErrorKind::Fail
 // fn from_context(input: I, ctx: &'static str) -> Self {
 //     Self { errors: vec![(input, VerboseErrorKind::Context(ctx))] }
-// }
\ No newline at end of file
+// }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_ctx() {
+        let input = "block{";
+        let err = VerboseError::from_context(&input[6..], "expected '}' found EOF")
+            .add_ctx(&input[5..], "block body")
+            .add_ctx(input, "block");
+        assert_eq!(
+            err.errors,
+            vec![
+                ("", VerboseErrorKind::Nom(ErrorKind::Fail)),
+                ("", VerboseErrorKind::Context("expected '}' found EOF")),
+                ("{", VerboseErrorKind::Context("block body")),
+                ("block{", VerboseErrorKind::Context("block")),
+            ]
+        );
+    }
+
+    #[test]
+    fn report_location() {
+        let input = "a{}\nb{\n\t\"é\" x";
+        let report = ParseErrorReport::new(input, &input[input.len() - 1..], "oops");
+        assert_eq!((report.offset, report.line, report.column), (input.len() - 1, 3, 6));
+        assert_eq!(report.to_string(), "line 3, column 6: oops");
+
+        let report = ParseErrorReport::new(input, input, "start");
+        assert_eq!((report.offset, report.line, report.column), (0, 1, 1));
+    }
+
+    #[test]
+    fn explain_error_types() {
+        let input = "block\n{\n\t\"no_value\"\n}";
+        let at_prop = "line 3, column 2";
+
+        let err = crate::parse::<&str, VerboseError<&str>>(input).unwrap_err();
+        assert!(explain_error(input, &err).starts_with(at_prop), "{}", explain_error(input, &err));
+
+        let err = crate::parse::<&str, nom::error::Error<&str>>(input).unwrap_err();
+        assert!(explain_error(input, &err).starts_with(at_prop), "{}", explain_error(input, &err));
+
+        let err = crate::parse::<&str, (&str, ErrorKind)>(input).unwrap_err();
+        assert!(explain_error(input, &err).starts_with(at_prop), "{}", explain_error(input, &err));
+
+        crate::parse::<&str, ()>(input).unwrap_err();
+        assert_eq!(explain_error(input, &()), "parse error at unknown location");
+
+        assert_eq!(explain(input, "}", ErrorKind::Tag), "line 4, column 1: Tag");
+    }
+}
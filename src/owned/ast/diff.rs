@@ -0,0 +1,89 @@
+//! Comparing the properties of two blocks.
+
+use super::*;
+
+/// Differences between the properties of two blocks, see [`Block::property_diff`].
+/// Each list is in the order the properties appear.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct PropertyDiff<'a> {
+    /// Key and value of properties only in the other block.
+    pub added: Vec<(&'a str, &'a str)>,
+    /// Key and value of properties only in this block.
+    pub removed: Vec<(&'a str, &'a str)>,
+    /// Key, old value, and new value of properties in both blocks with different values.
+    pub changed: Vec<(&'a str, &'a str, &'a str)>,
+}
+
+impl PropertyDiff<'_> {
+    /// Checks if there are no differences.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl<S: AsRef<str>> Block<S> {
+    /// Compares the properties of this block to `other` by key, not any of the children's
+    /// properties though. Only the first property with each key is compared.
+    pub fn property_diff<'a>(&'a self, other: &'a Block<S>) -> PropertyDiff<'a> {
+        fn first<'a, S: AsRef<str>>(block: &'a Block<S>, key: &str) -> Option<&'a str> {
+            block.props.iter().find(|prop| prop.key.as_ref() == key).map(|prop| prop.value.as_ref())
+        }
+
+        let mut diff = PropertyDiff::default();
+        let mut seen = HashSet::new();
+        for prop in self.props.iter() {
+            let (key, value) = (prop.key.as_ref(), prop.value.as_ref());
+            if !seen.insert(key) {
+                // a later duplicate
+                continue;
+            }
+            match first(other, key) {
+                Some(new) if new != value => diff.changed.push((key, value, new)),
+                Some(_) => {}
+                None => diff.removed.push((key, value)),
+            }
+        }
+        let mut seen = HashSet::new();
+        for prop in other.props.iter() {
+            let key = prop.key.as_ref();
+            if seen.insert(key) && first(self, key).is_none() {
+                diff.added.push((key, prop.value.as_ref()));
+            }
+        }
+        diff
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn property_diff() {
+        let vmf = crate::parse::<&str, ()>(
+            r#"entity { "id" "1" "classname" "light" "_light" "255 255 255 200" "style" "0" }
+            entity { "id" "1" "classname" "light" "_light" "255 0 0 200" "targetname" "red" }"#,
+        )
+        .unwrap();
+        let (old, new) = (&vmf.blocks[0], &vmf.blocks[1]);
+
+        let diff = old.property_diff(new);
+        assert_eq!(diff.changed, vec![("_light", "255 255 255 200", "255 0 0 200")]);
+        assert_eq!(diff.removed, vec![("style", "0")]);
+        assert_eq!(diff.added, vec![("targetname", "red")]);
+        assert!(!diff.is_empty());
+
+        assert!(old.property_diff(old).is_empty());
+    }
+
+    #[test]
+    fn property_diff_duplicate_keys() {
+        let vmf = crate::parse::<&str, ()>(
+            r#"entity { "k" "1" "k" "1" "gone" "a" "gone" "a" }
+            entity { "k" "2" "new" "b" "new" "b" }"#,
+        )
+        .unwrap();
+        let diff = vmf.blocks[0].property_diff(&vmf.blocks[1]);
+        assert_eq!(diff.changed, vec![("k", "1", "2")]);
+        assert_eq!(diff.removed, vec![("gone", "a")]);
+        assert_eq!(diff.added, vec![("new", "b")]);
+    }
+}
@@ -1,18 +1,26 @@
 use super::*;
+use crate::owned::parsers::nom_prelude::{ContextError, ParseError};
 use std::fmt::{self, Display, Write};
 
 // TODO: dyn or impl/trait, both work. Can be nested PadAdapter or bare formatter
 /// Helper struct for pretty printing struct like objects.
 /// When nested, each adapter keeps track wether it should print padding.
 /// See <https://github.com/rust-lang/rust/blob/master/library/core/src/fmt/builders.rs>
-struct PadAdapter<'a> {
+pub(super) struct PadAdapter<'a> {
     buf: &'a mut dyn Write,
     on_newline: bool,
+    levels: usize,
 }
 
 impl<'a> PadAdapter<'a> {
-    fn new(buf: &'a mut dyn Write) -> Self {
-        Self { buf, on_newline: false }
+    pub(super) fn new(buf: &'a mut dyn Write) -> Self {
+        Self { buf, on_newline: false, levels: 1 }
+    }
+
+    /// Pads every line with `levels` of padding, including the first,
+    /// see [`DisplayOptions::base_indent`].
+    fn with_levels(buf: &'a mut dyn Write, levels: usize) -> Self {
+        Self { buf, on_newline: true, levels }
     }
 }
 
@@ -20,7 +28,9 @@ impl fmt::Write for PadAdapter<'_> {
     fn write_str(&mut self, s: &str) -> fmt::Result {
         for s in s.split_inclusive('\n') {
             if self.on_newline {
-                self.buf.write_str(FMT_PADDING)?;
+                for _ in 0..self.levels {
+                    self.buf.write_str(FMT_PADDING)?;
+                }
             }
 
             self.on_newline = s.ends_with('\n');
@@ -31,6 +41,177 @@ impl fmt::Write for PadAdapter<'_> {
     }
 }
 
+/// Helper struct that drops every blank line after the first in a run,
+/// see [`DisplayOptions::collapse_blank_lines`].
+/// Buffers the current line so lines of only whitespace count as blank.
+/// Lines inside of `quote` quoted strings are always written, so values are never changed.
+struct CollapseAdapter<'a> {
+    buf: &'a mut dyn Write,
+    line: String,
+    last_blank: bool,
+    quote: char,
+    in_quote: bool,
+}
+
+impl<'a> CollapseAdapter<'a> {
+    fn new(buf: &'a mut dyn Write, quote: char) -> Self {
+        Self { buf, line: String::new(), last_blank: false, quote, in_quote: false }
+    }
+
+    /// Writes the last line if it does not end with a newline.
+    fn finish(self) -> fmt::Result {
+        self.buf.write_str(&self.line)
+    }
+}
+
+impl fmt::Write for CollapseAdapter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for s in s.split_inclusive('\n') {
+            self.line.push_str(s);
+            if !s.ends_with('\n') {
+                continue;
+            }
+
+            let started_in_quote = self.in_quote;
+            if self.line.matches(self.quote).count() % 2 == 1 {
+                self.in_quote = !self.in_quote;
+            }
+            let blank = !started_in_quote && self.line.trim().is_empty();
+            if !(blank && self.last_blank) {
+                self.buf.write_str(&self.line)?;
+            }
+            self.last_blank = blank;
+            self.line.clear();
+        }
+
+        Ok(())
+    }
+}
+
+/// Helper struct that drops leading and trailing whitespace from everything written to it.
+/// For writing block names without allocating, only whitespace that may be trailing is buffered.
+pub(super) struct TrimAdapter<'a> {
+    buf: &'a mut dyn Write,
+    started: bool,
+    pending: String,
+}
+
+impl<'a> TrimAdapter<'a> {
+    pub(super) fn new(buf: &'a mut dyn Write) -> Self {
+        Self { buf, started: false, pending: String::new() }
+    }
+}
+
+impl fmt::Write for TrimAdapter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let s = if self.started { s } else { s.trim_start() };
+        let trimmed = s.trim_end();
+        if !trimmed.is_empty() {
+            self.buf.write_str(&self.pending)?;
+            self.buf.write_str(trimmed)?;
+            self.pending.clear();
+            self.started = true;
+        }
+        if self.started {
+            self.pending.push_str(&s[trimmed.len()..]);
+        }
+        Ok(())
+    }
+}
+
+/// Applies the options that wrap the whole output, then calls `fmt`.
+fn fmt_adapted(
+    f: &mut dyn Write,
+    options: &DisplayOptions,
+    fmt: impl FnOnce(&mut dyn Write) -> fmt::Result,
+) -> fmt::Result {
+    if options.base_indent > 0 {
+        let mut adapter = PadAdapter::with_levels(f, options.base_indent);
+        return fmt_adapted(
+            &mut adapter,
+            &DisplayOptions { base_indent: 0, ..options.clone() },
+            fmt,
+        );
+    }
+    if options.collapse_blank_lines {
+        let mut adapter = CollapseAdapter::new(f, options.quote_char);
+        fmt(&mut adapter)?;
+        return adapter.finish();
+    }
+    fmt(f)
+}
+
+/// Options for formatting, see [`Vmf::to_string_with_options`].
+/// The default is the same as [`Display`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DisplayOptions {
+    /// Character to quote property keys and values with, `"` by default.
+    /// See [`parse_with_quote()`](crate::parse_with_quote) to parse it back.
+    pub quote_char: char,
+    /// When to quote property values, [`QuoteStyle::Always`] by default.
+    pub quote_style: QuoteStyle,
+    /// Pad property keys to the longest key in their block so values line up.
+    pub align_values: bool,
+    /// Escape tabs, newlines, carriage returns, and backslashes in keys and values as
+    /// `\t`, `\n`, `\r`, and `\\`. Other control characters are written as is.
+    /// See [`ParseConfig::unescape_control`](crate::parsers::ParseConfig::unescape_control)
+    /// to parse it back.
+    pub escape_control: bool,
+    /// Write at most one blank line in a row. Blank lines inside of quoted keys and
+    /// values are kept, so only the layout changes.
+    pub collapse_blank_lines: bool,
+    /// Write well-known root blocks in the order Hammer does: `versioninfo`, `visgroups`,
+    /// `viewsettings`, `world`, entities, `cameras`, then `cordon`. Other blocks are written
+    /// last. Blocks with the same rank keep their order.
+    pub canonical_block_order: bool,
+    /// Levels of padding to prefix every line with, including lines inside of values.
+    /// For embedding the output in another document. `0` by default.
+    pub base_indent: usize,
+}
+
+impl Default for DisplayOptions {
+    fn default() -> Self {
+        Self {
+            quote_char: '"',
+            quote_style: QuoteStyle::Always,
+            align_values: false,
+            escape_control: false,
+            collapse_blank_lines: false,
+            canonical_block_order: false,
+            base_indent: 0,
+        }
+    }
+}
+
+/// When to quote property values, see [`DisplayOptions::quote_style`].
+/// Keys are always quoted.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum QuoteStyle {
+    /// Quote every value, the only style [`parse()`](crate::parse) can read back.
+    #[default]
+    Always,
+    /// Leave a value unquoted if it is a single non-empty token of ASCII alphanumerics,
+    /// `_`, `-`, and `.`, like `0`, `-1.5`, or `info_player_start`.
+    /// Empty values and anything with whitespace, quotes, braces, or slashes stay quoted.
+    /// For tools that read unquoted KeyValues tokens.
+    Minimal,
+}
+
+impl QuoteStyle {
+    /// Whether `value`, as it will be written, needs to be quoted in this style.
+    pub fn needs_quotes(self, value: &str) -> bool {
+        match self {
+            QuoteStyle::Always => true,
+            QuoteStyle::Minimal => {
+                value.is_empty()
+                    || !value
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+            }
+        }
+    }
+}
+
 /// Stores the current max ids for [`Block::fmt_new_ids`]
 /// Does not store/mess with visgroup ids or group ids as those are referenced
 /// by the `Editor` info for entities
@@ -51,13 +232,83 @@ impl<S: Display + AsRef<str>> Vmf<S> {
     }
 }
 
+impl<S: Display> Vmf<S> {
+    /// Convert into a `String` using the given [`DisplayOptions`].
+    pub fn to_string_with_options(&self, options: &DisplayOptions) -> String {
+        let mut buf = String::new();
+        // writing to a String never fails
+        let _ = self.fmt_with_options(&mut buf, options);
+        buf
+    }
+
+    /// Convert into a `String` laid out like Hammer writes it: root blocks in
+    /// [canonical order](DisplayOptions::canonical_block_order), tab indentation,
+    /// and a trailing newline. Existing ids are kept.
+    pub fn to_pretty_string(&self) -> String {
+        let options = DisplayOptions { canonical_block_order: true, ..Default::default() };
+        let mut pretty = self.to_string_with_options(&options);
+        pretty.push('\n');
+        pretty
+    }
+
+    /// The [`Display`] implementation with [`DisplayOptions`].
+    pub fn fmt_with_options(&self, f: &mut dyn Write, options: &DisplayOptions) -> fmt::Result {
+        fmt_adapted(f, options, |f| self.fmt_options(f, options))
+    }
+
+    fn fmt_options(&self, f: &mut dyn Write, options: &DisplayOptions) -> fmt::Result {
+        let mut blocks: Vec<_> = self.inner.blocks.iter().collect();
+        if options.canonical_block_order {
+            // stable, so entities keep their order
+            blocks.sort_by_cached_key(|block| canonical_rank(&block.name.to_string()));
+        }
+
+        let mut iter = blocks.into_iter().peekable();
+        while let Some(block) = iter.next() {
+            block.fmt_options(f, options)?;
+            if iter.peek().is_some() {
+                // print newline if not last iteration
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Position of a root block in Hammer's output, see [`DisplayOptions::canonical_block_order`].
+fn canonical_rank(name: &str) -> u8 {
+    match name {
+        "versioninfo" => 0,
+        "visgroups" => 1,
+        "viewsettings" => 2,
+        "world" => 3,
+        "entity" | "hidden" => 4,
+        "cameras" => 5,
+        "cordon" | "cordons" => 6,
+        _ => 7,
+    }
+}
+
+impl<'a> Vmf<&'a str> {
+    /// Parse then [`Display`] the input, rewriting it with the default formatting.
+    /// Effectively a vmf formatter. Comments are discarded.
+    ///
+    /// See [`parse()`](crate::parse) for valid error types.
+    pub fn prettify<E>(input: &'a str) -> Result<String, E>
+    where
+        E: ParseError<&'a str> + ContextError<&'a str>,
+    {
+        crate::parse::<&str, E>(input).map(|vmf| vmf.to_string())
+    }
+}
+
 impl<S: Display + AsRef<str>> Block<S> {
     // TODO: dyn or impl, both work
     /// The [`Display`] alt implementation.
     /// Generates new ids for solids, sides, entities, and worlds.
     /// Disregards any existing id (id can be omitted).
     pub fn fmt_new_ids(&self, f: &mut dyn Write, state: &mut IdState) -> fmt::Result {
-        writeln!(f, "{}", self.name)?;
+        writeln!(f, "{}", self.name.as_ref().trim())?;
         let mut adapter = PadAdapter::new(f);
         writeln!(adapter, "{{")?;
 
@@ -81,7 +332,7 @@ impl<S: Display + AsRef<str>> Block<S> {
     /// increment id and write.
     fn write_new_id(&self, f: &mut dyn Write, state: &mut IdState) -> fmt::Result {
         // ugly
-        let new_id = match self.name.as_ref() {
+        let new_id = match self.name.as_ref().trim() {
             "world" => {
                 state.max_world_id += 1;
                 state.max_world_id
@@ -137,17 +388,52 @@ impl<S: Display + AsRef<str>> Display for Vmf<S> {
     }
 }
 
-impl<S: Display> Display for Block<S> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(f, "{}", self.name)?;
+impl<S: Display> Block<S> {
+    /// Convert into a `String` using the given [`DisplayOptions`].
+    pub fn to_string_with_options(&self, options: &DisplayOptions) -> String {
+        let mut buf = String::new();
+        // writing to a String never fails
+        let _ = self.fmt_with_options(&mut buf, options);
+        buf
+    }
+
+    /// Convert only the block at the path of indexes into each level's blocks into a `String`,
+    /// like [`Vmf::extract`] without cloning. An empty path converts this block.
+    /// Returns `None` if any index is out of bounds.
+    pub fn display_at(&self, path: &[usize]) -> Option<String> {
+        let block = path.iter().try_fold(self, |block, &index| block.blocks.get(index))?;
+        Some(block.to_string())
+    }
+
+    /// The [`Display`] implementation with [`DisplayOptions`].
+    pub fn fmt_with_options(&self, f: &mut dyn Write, options: &DisplayOptions) -> fmt::Result {
+        fmt_adapted(f, options, |f| self.fmt_options(f, options))
+    }
+
+    fn fmt_options(&self, f: &mut dyn Write, options: &DisplayOptions) -> fmt::Result {
+        // names cannot contain whitespace, so trim any that was added by accident
+        write!(TrimAdapter::new(f), "{}", self.name)?;
+        writeln!(f)?;
+
+        let key_width = if options.align_values {
+            self.props
+                .iter()
+                .map(|prop| prop.key_string(options).chars().count())
+                .max()
+                .unwrap_or(0)
+        } else {
+            0
+        };
 
         let mut adapter = PadAdapter::new(f);
         writeln!(adapter, "{{")?;
         for prop in self.props.iter() {
-            writeln!(adapter, "{prop}")?;
+            prop.fmt_padded(&mut adapter, options, key_width)?;
+            writeln!(adapter)?;
         }
         for block in self.blocks.iter() {
-            writeln!(adapter, "{block}")?;
+            block.fmt_options(&mut adapter, options)?;
+            writeln!(adapter)?;
         }
 
         write!(f, "}}")?;
@@ -155,9 +441,136 @@ impl<S: Display> Display for Block<S> {
     }
 }
 
+impl<K: Display, V: Display> Property<K, V> {
+    /// The [`Display`] implementation with [`DisplayOptions`].
+    pub fn fmt_with_options(&self, f: &mut dyn Write, options: &DisplayOptions) -> fmt::Result {
+        self.fmt_padded(f, options, 0)
+    }
+
+    /// Pads the key with spaces to `key_width` characters, not including quotes.
+    fn fmt_padded(
+        &self,
+        f: &mut dyn Write,
+        options: &DisplayOptions,
+        key_width: usize,
+    ) -> fmt::Result {
+        let quote = options.quote_char;
+        if key_width == 0 && !options.escape_control {
+            write!(f, "{quote}{}{quote} ", self.key)?;
+        } else {
+            let key = self.key_string(options);
+            let padding = key_width.saturating_sub(key.chars().count());
+            write!(f, "{quote}{key}{quote} {:padding$}", "")?;
+        }
+        if !options.escape_control && options.quote_style == QuoteStyle::Always {
+            return write!(f, "{quote}{}{quote}", self.value);
+        }
+
+        let value = if options.escape_control {
+            escape_control(&self.value.to_string())
+        } else {
+            self.value.to_string()
+        };
+        if options.quote_style.needs_quotes(&value) {
+            write!(f, "{quote}{value}{quote}")
+        } else {
+            write!(f, "{value}")
+        }
+    }
+
+    /// The key as it will be displayed, without quotes.
+    fn key_string(&self, options: &DisplayOptions) -> String {
+        if options.escape_control {
+            escape_control(&self.key.to_string())
+        } else {
+            self.key.to_string()
+        }
+    }
+}
+
+/// Escapes tabs, newlines, carriage returns, and backslashes, see [`DisplayOptions::escape_control`].
+fn escape_control(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\\' => escaped.push_str("\\\\"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+impl<S: Display + AsRef<str>> Block<S> {
+    /// Convert into a `String` like [`to_string_checked`](Block::to_string_checked),
+    /// erroring with only the first [`ValidationWarning`].
+    pub fn try_to_string(&self) -> Result<String, ValidationWarning> {
+        self.to_string_checked().map_err(|mut err| err.warnings.swap_remove(0))
+    }
+}
+
+impl<S: Display + AsRef<str>> Vmf<S> {
+    /// Convert into a `String`, erroring with every [`ValidationWarning`] if the output
+    /// would not parse again, see [`Block::validate`].
+    pub fn to_string_checked(&self) -> Result<String, DisplayError> {
+        DisplayError::check(self)?;
+        Ok(self.to_string())
+    }
+}
+
+impl<S: Display + AsRef<str>> Block<S> {
+    /// Convert into a `String`, erroring with every [`ValidationWarning`] if the output
+    /// would not parse again, see [`Block::validate`].
+    pub fn to_string_checked(&self) -> Result<String, DisplayError> {
+        DisplayError::check(self)?;
+        Ok(self.to_string())
+    }
+}
+
+/// Error for a tree that would not display as valid vmf, see [`Block::to_string_checked`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DisplayError {
+    pub warnings: Vec<ValidationWarning>,
+}
+
+impl DisplayError {
+    fn check<S: AsRef<str>>(block: &Block<S>) -> Result<(), Self> {
+        let warnings = block.validate();
+        if warnings.is_empty() {
+            Ok(())
+        } else {
+            Err(Self { warnings })
+        }
+    }
+}
+
+impl Display for DisplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "output would not parse:")?;
+        for warning in self.warnings.iter() {
+            write!(f, " {warning};")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for DisplayError {}
+
+/// Writes names, keys, and values as is. Empty block names and quotes in keys or values
+/// will not parse again, see [`Block::try_to_string`] and [`Block::validate`].
+impl<S: Display> Display for Block<S> {
+    /// Leading and trailing whitespace in block names is not written, as it would not parse.
+    /// Other invalid names are written as is, see [`Block::validate`].
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_with_options(f, &DisplayOptions::default())
+    }
+}
+
 impl<K: Display, V: Display> Display for Property<K, V> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "\"{}\" \"{}\"", self.key, self.value)
+        self.fmt_with_options(f, &DisplayOptions::default())
     }
 }
 
@@ -200,4 +613,369 @@ entity { "id" "2" entity { "id" "3" } }
         eprintln!("{output_str}");
         assert_eq!(truth, output);
     }
+
+    #[test]
+    fn prettify() {
+        use super::Vmf;
+
+        let pretty = "ClassName_1
+{
+\t\"Property_1\" \"Value_1\"
+\tClassName_2
+\t{
+\t}
+}
+ClassName_3
+{
+}";
+        let ugly =
+            "// comment\nClassName_1{ \"Property_1\"\"Value_1\" ClassName_2 {}}  ClassName_3{}";
+
+        assert_eq!(Vmf::prettify::<()>(pretty).unwrap(), pretty);
+        assert_eq!(Vmf::prettify::<()>(ugly).unwrap(), pretty);
+        let twice = Vmf::prettify::<()>(&Vmf::prettify::<()>(ugly).unwrap()).unwrap();
+        assert_eq!(twice, pretty);
+        assert!(Vmf::prettify::<()>("ClassName_1{").is_err());
+    }
+
+    #[test]
+    fn quote_char() {
+        use super::{DisplayOptions, Property, Vmf};
+
+        let input = "ClassName_1
+{
+\t\"Property_1\" \"Value's\"
+\tClassName_2
+\t{
+\t\t\"Property_2\" \"\"
+\t}
+}";
+        let truth = "ClassName_1
+{
+\t'Property_1' 'Value\"s'
+\tClassName_2
+\t{
+\t\t'Property_2' ''
+\t}
+}";
+        let options = DisplayOptions { quote_char: '\'', ..Default::default() };
+
+        let mut vmf = crate::parse::<String, ()>(input).unwrap();
+        vmf.blocks[0].props[0].value = "Value\"s".to_string();
+        let output = vmf.to_string_with_options(&options);
+        assert_eq!(output, truth);
+
+        let single = crate::parse_with_quote::<String, ()>(&output, '\'').unwrap();
+        assert_eq!(single, vmf);
+        assert_eq!(single.to_string(), vmf.to_string());
+        assert!(crate::parse::<&str, ()>(&output).is_err());
+
+        let prop = Property::<&str, &str>::new("key", "value");
+        let mut buf = String::new();
+        prop.fmt_with_options(&mut buf, &options).unwrap();
+        assert_eq!(buf, "'key' 'value'");
+        assert_eq!(Vmf::prettify::<()>(input).unwrap(), input);
+    }
+
+    #[test]
+    fn align_values() {
+        use super::DisplayOptions;
+
+        let input = r#"entity { "id" "1" "classname" "light" "_light" "255 255 255 200" editor { "color" "220 30 220" } }"#;
+        let truth = "entity
+{
+\t\"id\"        \"1\"
+\t\"classname\" \"light\"
+\t\"_light\"    \"255 255 255 200\"
+\teditor
+\t{
+\t\t\"color\" \"220 30 220\"
+\t}
+}";
+        let vmf = crate::parse::<&str, ()>(input).unwrap();
+        let options = DisplayOptions { align_values: true, ..Default::default() };
+        let output = vmf.to_string_with_options(&options);
+        assert_eq!(output, truth);
+        assert_eq!(crate::parse::<&str, ()>(&output).unwrap(), vmf);
+    }
+
+    #[test]
+    fn escape_control() {
+        use super::{DisplayOptions, Property};
+        use crate::parsers::ParseConfig;
+
+        let mut vmf = crate::parse::<String, ()>("entity{}").unwrap();
+        vmf.blocks[0].props.push(Property::new("message", "tab\there\nnewline C:\\path"));
+        let options = DisplayOptions { escape_control: true, ..Default::default() };
+
+        let output = vmf.to_string_with_options(&options);
+        assert_eq!(output, "entity\n{\n\t\"message\" \"tab\\there\\nnewline C:\\\\path\"\n}");
+
+        let config = ParseConfig::default().with_unescape_control(true);
+        let parsed = crate::parse_with_config_owned::<String>(&output, &config).unwrap();
+        assert_eq!(parsed, vmf);
+    }
+
+    #[test]
+    fn empty_name() {
+        use super::{Block, Property, ValidationWarning};
+
+        let mut block: Block<String> = crate::block!(world { solid {} });
+        assert_eq!(block.try_to_string().unwrap(), "world\n{\n\tsolid\n\t{\n\t}\n}");
+
+        block.blocks[0].name = String::new();
+        assert_eq!(block.try_to_string(), Err(ValidationWarning::EmptyName));
+        // displaying anyway does not round trip
+        assert!(crate::parse::<&str, ()>(&block.to_string()).is_err());
+
+        // same rules as to_string_checked
+        block.blocks[0].name = "solid".to_string();
+        block.props.push(Property::new("key", "\""));
+        let warning = ValidationWarning::QuoteInValue { key: "key".into(), value: "\"".into() };
+        assert_eq!(block.try_to_string(), Err(warning.clone()));
+        assert_eq!(block.to_string_checked().unwrap_err().warnings, vec![warning]);
+    }
+
+    #[test]
+    fn collapse_blank_lines() {
+        use super::{Block, DisplayOptions, Property};
+        use std::fmt::Write;
+
+        let mut block: Block<String> = crate::block!(world {
+            solid { side {} side {} }
+            solid {}
+            group { editor {} }
+        });
+        let options = DisplayOptions { collapse_blank_lines: true, ..Default::default() };
+        // nested empty blocks already have no blank lines
+        assert_eq!(block.to_string(), block.to_string_with_options(&options));
+
+        // values are never changed
+        block.props.push(Property::new("comments", "first\n\n\n\nsecond\n \n\t\nthird"));
+        let output = block.to_string_with_options(&options);
+        assert_eq!(output, block.to_string());
+
+        // blank lines outside of quotes are collapsed
+        let mut output = String::new();
+        let mut adapter = super::CollapseAdapter::new(&mut output, '"');
+        adapter.write_str("a\n\n \n\t\n\"b\n\n\n\" \"\"\n\n\nc").unwrap();
+        adapter.finish().unwrap();
+        assert_eq!(output, "a\n\n\"b\n\n\n\" \"\"\n\nc");
+    }
+
+    #[test]
+    fn canonical_block_order() {
+        use super::DisplayOptions;
+
+        let vmf = crate::parse::<&str, ()>(
+            r#"cordon {} entity { "id" "2" } world {} custom {} cameras {}
+            entity { "id" "3" } viewsettings {} versioninfo {} visgroups {}"#,
+        )
+        .unwrap();
+        let options = DisplayOptions { canonical_block_order: true, ..Default::default() };
+        let output = vmf.to_string_with_options(&options);
+
+        let reparsed = crate::parse::<&str, ()>(&output).unwrap();
+        let names: Vec<_> = reparsed.blocks.iter().map(|block| block.name).collect();
+        assert_eq!(
+            names,
+            vec![
+                "versioninfo",
+                "visgroups",
+                "viewsettings",
+                "world",
+                "entity",
+                "entity",
+                "cameras",
+                "cordon",
+                "custom"
+            ]
+        );
+        assert_eq!(reparsed.blocks[4].props[0].value, "2");
+        assert_eq!(reparsed.blocks[5].props[0].value, "3");
+
+        // off by default
+        assert_eq!(vmf.to_string_with_options(&DisplayOptions::default()), vmf.to_string());
+    }
+
+    #[test]
+    fn trim_block_names() {
+        use std::fmt::Write;
+
+        let mut vmf = crate::parse::<String, ()>("solid\n{\n\t\"id\" \"1\"\n}").unwrap();
+        let truth = vmf.to_string();
+        vmf.blocks[0].name = "solid ".to_string();
+        assert_eq!(vmf.to_string(), truth);
+        assert_eq!(format!("{vmf:#}"), truth);
+
+        vmf.blocks[0].name = "\tsolid\n".to_string();
+        assert_eq!(crate::parse::<&str, ()>(&vmf.to_string()).unwrap().blocks[0].name, "solid");
+        assert_eq!(vmf.validate(), vec![]);
+        assert_eq!(vmf.to_string_checked(), Ok(truth.clone()));
+        assert_eq!(vmf.blocks[0].try_to_string(), Ok(truth));
+
+        // whitespace only is empty
+        vmf.blocks[0].name = " \t".to_string();
+        assert_eq!(vmf.blocks[0].try_to_string(), Err(super::ValidationWarning::EmptyName));
+        assert_eq!(vmf.validate(), vec![super::ValidationWarning::EmptyName]);
+        assert!(vmf.to_string_checked().is_err());
+
+        // written in several pieces
+        let mut output = String::new();
+        let mut adapter = super::TrimAdapter::new(&mut output);
+        for piece in [" ", "a b", " ", "\tc ", "\t"] {
+            adapter.write_str(piece).unwrap();
+        }
+        assert_eq!(output, "a b \tc");
+    }
+
+    #[test]
+    fn base_indent() {
+        use super::DisplayOptions;
+
+        let vmf = crate::parse::<&str, ()>("world { \"id\" \"1\" solid {} } entity {}").unwrap();
+        let options = DisplayOptions { base_indent: 2, ..Default::default() };
+        let output = vmf.to_string_with_options(&options);
+
+        assert!(output.lines().all(|line| line.starts_with("\t\t")), "{output}");
+        let unindented: Vec<_> = output.lines().map(|line| &line[2..]).collect();
+        assert_eq!(unindented.join("\n"), vmf.to_string());
+
+        // with collapsed blank lines too
+        let options = DisplayOptions { collapse_blank_lines: true, ..options };
+        assert_eq!(vmf.to_string_with_options(&options), output);
+        assert_eq!(vmf.blocks[1].to_string_with_options(&options), "\t\tentity\n\t\t{\n\t\t}");
+    }
+
+    #[test]
+    fn to_string_checked() {
+        use super::{DisplayError, ValidationWarning};
+
+        let mut vmf =
+            crate::parse::<String, ()>(r#"world { "id" "1" solid { "id" "2" } }"#).unwrap();
+        assert_eq!(vmf.to_string_checked(), Ok(vmf.to_string()));
+        assert_eq!(vmf.blocks[0].to_string_checked(), Ok(vmf.blocks[0].to_string()));
+
+        vmf.blocks[0].blocks[0].props[0].value = "say \"hi\"".to_string();
+        let err = vmf.to_string_checked().unwrap_err();
+        assert_eq!(
+            err,
+            DisplayError {
+                warnings: vec![ValidationWarning::QuoteInValue {
+                    key: "id".to_string(),
+                    value: "say \"hi\"".to_string()
+                }]
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            r#"output would not parse: quote in value "say \"hi\"" of property "id";"#
+        );
+        // displaying anyway does not round trip
+        assert_ne!(
+            crate::parse::<&str, ()>(&vmf.to_string()).ok().map(|v| v.into_owned()),
+            Some(vmf)
+        );
+    }
+
+    #[test]
+    fn to_pretty_string() {
+        let hammer = "versioninfo
+{
+\t\"editorversion\" \"400\"
+\t\"formatversion\" \"100\"
+}
+visgroups
+{
+}
+viewsettings
+{
+\t\"bSnapToGrid\" \"1\"
+}
+world
+{
+\t\"id\" \"1\"
+\t\"classname\" \"worldspawn\"
+\tsolid
+\t{
+\t\t\"id\" \"2\"
+\t\tside
+\t\t{
+\t\t\t\"id\" \"1\"
+\t\t\t\"plane\" \"(0 0 0) (0 64 0) (64 64 0)\"
+\t\t}
+\t}
+}
+entity
+{
+\t\"id\" \"5\"
+\t\"classname\" \"info_player_start\"
+}
+cameras
+{
+\t\"activecamera\" \"-1\"
+}
+cordon
+{
+\t\"active\" \"0\"
+}
+";
+        let vmf = crate::parse::<&str, ()>(hammer).unwrap();
+        assert_eq!(vmf.to_pretty_string(), hammer);
+
+        // shuffled and badly formatted
+        let messy = "cordon{\"active\" \"0\"} cameras {\"activecamera\" \"-1\"}
+            entity{\"id\" \"5\" \"classname\" \"info_player_start\"}
+            world{\"id\" \"1\" \"classname\" \"worldspawn\"
+            solid{\"id\" \"2\" side{\"id\" \"1\" \"plane\" \"(0 0 0) (0 64 0) (64 64 0)\"}}}
+            viewsettings{\"bSnapToGrid\" \"1\"} visgroups{}
+            versioninfo{\"editorversion\" \"400\" \"formatversion\" \"100\"}";
+        let pretty = crate::parse::<&str, ()>(messy).unwrap().to_pretty_string();
+        assert_eq!(pretty, hammer);
+        // idempotent
+        assert_eq!(crate::parse::<&str, ()>(&pretty).unwrap().to_pretty_string(), pretty);
+    }
+
+    #[test]
+    fn minimal_quotes() {
+        use super::{DisplayOptions, Property, QuoteStyle};
+
+        let options = DisplayOptions { quote_style: QuoteStyle::Minimal, ..Default::default() };
+        let display = |value: &str| {
+            let mut output = String::new();
+            Property::<&str, &str>::new("key", value)
+                .fmt_with_options(&mut output, &options)
+                .unwrap();
+            output
+        };
+        assert_eq!(display(""), r#""key" """#, "empty stays quoted");
+        assert_eq!(display(" "), r#""key" " ""#);
+        assert_eq!(display("two words"), r#""key" "two words""#);
+        assert_eq!(display("a\tb"), "\"key\" \"a\tb\"");
+        assert_eq!(display("{}"), r#""key" "{}""#);
+        assert_eq!(display("models/tree.mdl"), r#""key" "models/tree.mdl""#);
+
+        assert_eq!(display("info_player_start"), r#""key" info_player_start"#);
+        assert_eq!(display("-1.5"), r#""key" -1.5"#);
+        assert_eq!(display("0"), r#""key" 0"#);
+
+        let options = DisplayOptions { escape_control: true, ..options };
+        let mut output = String::new();
+        Property::<&str, &str>::new("key", "a\tb").fmt_with_options(&mut output, &options).unwrap();
+        assert_eq!(output, r#""key" "a\tb""#, "escapes are never a single token");
+    }
+
+    #[test]
+    fn display_at() {
+        let vmf = crate::parse::<&str, ()>(
+            r#"versioninfo {}
+            world { "id" "1" solid { "id" "2" side { "id" "3" } side { "id" "4" } } }"#,
+        )
+        .unwrap();
+        assert_eq!(vmf.display_at(&[1, 0, 1]).unwrap(), "side\n{\n\t\"id\" \"4\"\n}");
+        assert_eq!(vmf.blocks[1].display_at(&[0, 0]).unwrap(), "side\n{\n\t\"id\" \"3\"\n}");
+        assert_eq!(vmf.blocks[0].display_at(&[]).unwrap(), "versioninfo\n{\n}");
+        assert_eq!(vmf.display_at(&[1, 0, 2]), None);
+        assert_eq!(vmf.display_at(&[2]), None);
+    }
 }
@@ -1,10 +1,23 @@
 //! Abstract syntax tree representing a vmf file.
 
+mod diff;
 mod display;
+mod ordered;
+mod output;
+mod spanned;
+mod validate;
 
+pub use diff::*;
 pub use display::*;
+pub use ordered::*;
+pub use output::*;
+pub use spanned::*;
+pub use validate::*;
 
-use std::ops::{Deref, DerefMut};
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::ops::{ControlFlow, Deref, DerefMut};
+use std::str::FromStr;
 
 /// Padding for [`PadAdapter`]
 const FMT_PADDING: &str = "\t";
@@ -40,6 +53,9 @@ pub struct Property<K, V> {
 
 impl<S> Vmf<S> {
     pub const ROOT_NAME: &str = "root";
+    /// Names of blocks only used by Hammer, see [`strip_editor_metadata`](Self::strip_editor_metadata).
+    pub const EDITOR_BLOCK_NAMES: &[&str] =
+        &["editor", "visgroups", "cameras", "cordon", "cordons"];
 
     /// Returns the root block. You can also use `.as_ref()` or Deref coercion.
     pub fn root(&self) -> &Block<S> {
@@ -52,10 +68,208 @@ impl<S> Vmf<S> {
     }
 }
 
+impl<S: AsRef<str>> Vmf<S> {
+    /// Removes root `entity` blocks that have no properties and no blocks.
+    pub fn remove_empty_entities(&mut self) {
+        self.inner.blocks.retain(|block| {
+            block.name.as_ref() != "entity" || !block.props.is_empty() || !block.blocks.is_empty()
+        });
+    }
+
+    /// Removes the root `visgroups` blocks and every `visgroupid` property in `editor` blocks.
+    pub fn remove_visgroups(&mut self) {
+        self.inner.blocks.retain(|block| block.name.as_ref() != "visgroups");
+        fn remove_ids<S: AsRef<str>>(block: &mut Block<S>) {
+            if block.name.as_ref() == "editor" {
+                block.props.retain(|prop| prop.key.as_ref() != "visgroupid");
+            }
+            block.blocks.iter_mut().for_each(remove_ids);
+        }
+        remove_ids(&mut self.inner);
+    }
+
+    /// Recursively removes every block named in [`EDITOR_BLOCK_NAMES`](Self::EDITOR_BLOCK_NAMES),
+    /// like the `editor` block inside of solids and entities. For exporting to other tools.
+    pub fn strip_editor_metadata(&mut self) {
+        fn strip<S: AsRef<str>>(block: &mut Block<S>) {
+            block
+                .blocks
+                .retain(|child| !Vmf::<S>::EDITOR_BLOCK_NAMES.contains(&child.name.as_ref()));
+            block.blocks.iter_mut().for_each(strip);
+        }
+        strip(&mut self.inner);
+    }
+
+    /// Appends the root blocks of `other` to this `Vmf`.
+    /// With [`MergeOptions::fuse_worlds`], the sub blocks of any `world` in `other` are
+    /// moved into the first `world` of this one instead, keeping only this world's properties.
+    pub fn merge(&mut self, other: Vmf<S>, options: &MergeOptions) {
+        for block in other.inner.blocks {
+            let world = if options.fuse_worlds && block.name.as_ref() == "world" {
+                self.inner.blocks.iter_mut().find(|block| block.name.as_ref() == "world")
+            } else {
+                None
+            };
+            match world {
+                Some(world) => world.blocks.extend(block.blocks),
+                None => self.inner.blocks.push(block),
+            }
+        }
+    }
+
+    /// Iterates over every `side` block, wherever it is, in the order they appear.
+    /// Does not look inside of `side` blocks, the same as [`sides_mut`](Self::sides_mut).
+    pub fn sides(&self) -> impl Iterator<Item = &Block<S>> {
+        fn collect<'a, S: AsRef<str>>(block: &'a Block<S>, sides: &mut Vec<&'a Block<S>>) {
+            for child in block.blocks.iter() {
+                if child.name.as_ref() == "side" {
+                    sides.push(child);
+                } else {
+                    collect(child, sides);
+                }
+            }
+        }
+
+        let mut sides = Vec::new();
+        collect(&self.inner, &mut sides);
+        sides.into_iter()
+    }
+
+    /// Iterates mutably over every `side` block, wherever it is, in the order they appear.
+    /// For editing materials and texture axes. Does not look inside of `side` blocks,
+    /// as a side and a side nested in it cannot both be borrowed mutably.
+    pub fn sides_mut(&mut self) -> impl Iterator<Item = &mut Block<S>> {
+        fn collect<'a, S: AsRef<str>>(block: &'a mut Block<S>, sides: &mut Vec<&'a mut Block<S>>) {
+            for child in block.blocks.iter_mut() {
+                if child.name.as_ref() == "side" {
+                    sides.push(child);
+                } else {
+                    collect(child, sides);
+                }
+            }
+        }
+
+        let mut sides = Vec::new();
+        collect(&mut self.inner, &mut sides);
+        sides.into_iter()
+    }
+
+    /// Returns every distinct `material` of every `side` block, wherever it is.
+    /// Materials are compared exactly, so differently cased names are counted separately.
+    pub fn unique_materials(&self) -> HashSet<&str> {
+        self.inner
+            .iter_tree()
+            .filter(|block| block.name.as_ref() == "side")
+            .flat_map(|side| side.props.iter())
+            .filter(|prop| prop.key.as_ref() == "material")
+            .map(|prop| prop.value.as_ref())
+            .collect()
+    }
+
+    /// Returns a human readable summary of the map, for command line tools.
+    /// Counts solids, their sides, and entities, then entities per classname sorted by name.
+    pub fn stats_report(&self) -> String {
+        use std::fmt::Write;
+
+        let entities = self.flatten_entities();
+        let mut classnames = std::collections::BTreeMap::new();
+        for entity in entities.iter() {
+            let classname =
+                entity.get_property_ci("classname").map_or("<none>", |p| p.value.as_ref());
+            *classnames.entry(classname).or_insert(0) += 1;
+        }
+
+        let sides: usize = self
+            .solids()
+            .map(|solid| solid.blocks.iter().filter(|b| b.name.as_ref() == "side").count())
+            .sum();
+
+        let mut report = String::new();
+        // writing to a String never fails
+        let _ = writeln!(report, "solids: {}", self.solids().count());
+        let _ = writeln!(report, "sides: {sides}");
+        let _ = writeln!(report, "entities: {}", entities.len());
+        for (classname, count) in classnames {
+            let _ = writeln!(report, "\t{classname}: {count}");
+        }
+        report
+    }
+
+    /// Consumes the `Vmf`, grouping its root blocks by name. Blocks keep their order
+    /// within each group.
+    pub fn into_block_map(self) -> HashMap<String, Vec<Block<S>>> {
+        let mut map: HashMap<String, Vec<Block<S>>> = HashMap::new();
+        for block in self.inner.blocks {
+            map.entry(block.name.as_ref().to_string()).or_default().push(block);
+        }
+        map
+    }
+
+    /// Iterates over every `solid` block, in the `world`, in brush entities,
+    /// and in `hidden` wrappers, in the order they appear.
+    pub fn solids(&self) -> impl Iterator<Item = &Block<S>> {
+        self.inner.iter_tree().filter(|block| block.name.as_ref() == "solid")
+    }
+}
+
+impl<S: AsRef<str> + From<String>> Vmf<S> {
+    /// Renumbers the ids of root `entity` blocks, including hidden ones, counting up from `start`.
+    /// Unlike [`Vmf::to_string_new_ids`], solids, sides, and worlds are left untouched,
+    /// even solids inside brush entities. Entities without an id get one.
+    pub fn reindex_entity_ids(&mut self, start: i32) {
+        let entities = self.inner.blocks.iter_mut().flat_map(|block| {
+            if block.name.as_ref() == "hidden" {
+                block.blocks.iter_mut()
+            } else {
+                std::slice::from_mut(block).iter_mut()
+            }
+        });
+
+        let entities = entities.filter(|block| block.name.as_ref() == "entity");
+        for (entity, id) in entities.zip(start..) {
+            let id = S::from(id.to_string());
+            match entity.props.iter_mut().find(|prop| prop.is_id()) {
+                Some(prop) => prop.value = id,
+                None => {
+                    entity.props.insert(0, Property { key: S::from("id".to_string()), value: id })
+                }
+            }
+        }
+    }
+}
+
 impl<'a, S: From<&'a str>> Vmf<S> {
     pub fn new(blocks: Vec<Block<S>>) -> Self {
         Self { inner: Block::new(Self::ROOT_NAME, vec![], blocks) }
     }
+
+    /// Clones the block at the path of indexes into each level's blocks,
+    /// returning it as the only block of a new `Vmf`. For exporting a selection.
+    /// An empty path clones the whole `Vmf`.
+    pub fn extract(&self, path: &[usize]) -> Option<Vmf<S>>
+    where
+        S: Clone,
+    {
+        if path.is_empty() {
+            return Some(self.clone());
+        }
+        let block = path.iter().try_fold(&self.inner, |block, &index| block.blocks.get(index))?;
+        Some(Vmf::new(vec![block.clone()]))
+    }
+}
+
+impl Vmf<&str> {
+    /// Converts every borrowed string into a `String`, consuming `self`.
+    pub fn into_owned(self) -> Vmf<String> {
+        Vmf { inner: self.inner.into_owned() }
+    }
+}
+
+impl Block<&str> {
+    /// Converts every borrowed string into a `String`, consuming `self`.
+    pub fn into_owned(self) -> Block<String> {
+        self.map_strings(&mut String::from)
+    }
 }
 
 impl<S> Block<S> {
@@ -63,11 +277,638 @@ impl<S> Block<S> {
         Self { name: name.into(), props, blocks }
     }
 
+    /// Replaces all properties of this block, for chaining when building a block.
+    pub fn with_properties(mut self, props: Vec<Property<S, S>>) -> Self {
+        self.props = props;
+        self
+    }
+
+    /// Replaces all sub blocks of this block, for chaining when building a block.
+    pub fn with_blocks(mut self, blocks: Vec<Block<S>>) -> Self {
+        self.blocks = blocks;
+        self
+    }
+
+    /// Replaces the name of this block. See [`Block::try_set_name`] to check it first.
+    pub fn set_name(&mut self, name: impl Into<S>) {
+        self.name = name.into();
+    }
+
+    /// Returns the properties of this block. Not any of the children's properties though.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use vmf_parser_nom::ast::Property;
+    /// use vmf_parser_nom::parse;
+    ///
+    /// let vmf = parse::<&str, ()>(r#"entity { "classname" "light" editor {} }"#).unwrap();
+    /// let entity = &vmf.blocks()[0];
+    /// assert_eq!(entity.properties(), &[Property::new("classname", "light")]);
+    /// assert_eq!(entity.blocks()[0].name, "editor");
+    /// assert!(entity.blocks()[0].properties().is_empty());
+    /// ```
+    pub fn properties(&self) -> &[Property<S, S>] {
+        &self.props
+    }
+
+    /// Returns the sub blocks of this block. Not any of the children's children though.
+    /// See [`Block::properties`] for an example.
+    pub fn blocks(&self) -> &[Block<S>] {
+        &self.blocks
+    }
+
+    /// Returns the number of properties in this block. Not any of the children's properties though.
+    pub fn num_properties(&self) -> usize {
+        self.props.len()
+    }
+
+    /// Returns the number of sub blocks in this block. Not any of the children's children though.
+    pub fn num_blocks(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// Takes the properties out of this block, leaving it with none.
+    pub fn take_properties(&mut self) -> Vec<Property<S, S>> {
+        std::mem::take(&mut self.props)
+    }
+
+    /// Takes the sub blocks out of this block, leaving it with none.
+    pub fn take_blocks(&mut self) -> Vec<Block<S>> {
+        std::mem::take(&mut self.blocks)
+    }
+
+    /// Counts every child block, and their children, that match the predicate.
+    /// Does not include this block.
+    pub fn count_matching(&self, mut pred: impl FnMut(&Block<S>) -> bool) -> usize {
+        self.iter_tree().skip(1).filter(|block| pred(block)).count()
+    }
+
+    /// Iterates depth first over this block and all of its children, parents before children.
+    pub(crate) fn iter_tree(&self) -> impl Iterator<Item = &Self> {
+        let mut stack = vec![self];
+        std::iter::from_fn(move || {
+            let block = stack.pop()?;
+            stack.extend(block.blocks.iter().rev());
+            Some(block)
+        })
+    }
+
+    /// Calls `f` on this block and all of its children depth first, parents before children,
+    /// stopping as soon as `f` returns [`ControlFlow::Break`].
+    pub fn walk<B>(&self, mut f: impl FnMut(&Block<S>) -> ControlFlow<B>) -> ControlFlow<B> {
+        self.walk_impl(&mut f)
+    }
+
+    fn walk_impl<B>(&self, f: &mut impl FnMut(&Block<S>) -> ControlFlow<B>) -> ControlFlow<B> {
+        f(self)?;
+        for block in self.blocks.iter() {
+            block.walk_impl(f)?;
+        }
+        ControlFlow::Continue(())
+    }
+
+    /// Converts every name, key, and value in the tree, in the order they appear in the input.
+    pub(crate) fn map_strings<T>(self, f: &mut impl FnMut(S) -> T) -> Block<T> {
+        Block {
+            name: f(self.name),
+            props: self
+                .props
+                .into_iter()
+                .map(|prop| {
+                    let key = f(prop.key);
+                    Property { key, value: f(prop.value) }
+                })
+                .collect(),
+            blocks: self.blocks.into_iter().map(|block| block.map_strings(f)).collect(),
+        }
+    }
+
+    /// Converts every property value in the tree with `f`, and every name and key with `From`.
+    pub(crate) fn map_values<T: From<S>>(self, f: &mut impl FnMut(S) -> T) -> Block<T> {
+        Block {
+            name: self.name.into(),
+            props: self
+                .props
+                .into_iter()
+                .map(|prop| Property { key: prop.key.into(), value: f(prop.value) })
+                .collect(),
+            blocks: self.blocks.into_iter().map(|block| block.map_values(f)).collect(),
+        }
+    }
+
     /// Iterates over the sub blocks of this block. Not any of the children's children though.
     /// [`traverse`](crate::traverse) uses this. TODO:
     pub fn iter_children(&self) -> impl Iterator<Item = &Self> {
         self.blocks.iter()
     }
+
+    /// Iterates over the sub blocks of this block in reverse order.
+    /// Not any of the children's children though.
+    pub fn iter_children_rev(&self) -> impl DoubleEndedIterator<Item = &Self> {
+        self.blocks.iter().rev()
+    }
+
+    /// Iterates over the properties of this block. Not any of the children's properties though.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use vmf_parser_nom::parse;
+    ///
+    /// let vmf = parse::<&str, ()>(r#"entity { "id" "1" "classname" "light" editor { "color" "0 0 0" } }"#).unwrap();
+    /// let keys: Vec<_> = vmf.blocks[0].iter_properties().map(|prop| prop.key).collect();
+    /// assert_eq!(keys, vec!["id", "classname"]);
+    /// ```
+    pub fn iter_properties(&self) -> impl Iterator<Item = &Property<S, S>> {
+        self.props.iter()
+    }
+}
+
+impl<S: AsRef<str>> Block<S> {
+    /// Returns every distinct property key in this block and all of its children.
+    /// For finding out what keyvalues a map uses.
+    pub fn all_property_keys(&self) -> HashSet<&str> {
+        self.iter_tree()
+            .flat_map(|block| block.props.iter())
+            .map(|prop| prop.key.as_ref())
+            .collect()
+    }
+
+    /// Like [`Block::set_name`] but errors if the name is empty or contains anything
+    /// other than ASCII alphanumerics and underscores, which would display as unparseable output.
+    /// The name is left unchanged on error.
+    pub fn try_set_name(&mut self, name: impl Into<S>) -> Result<(), InvalidNameError> {
+        let name = name.into();
+        if name.as_ref().is_empty() || !validate::is_valid_name(name.as_ref()) {
+            return Err(InvalidNameError { name: name.as_ref().to_string() });
+        }
+        self.name = name;
+        Ok(())
+    }
+
+    /// Merges every child block named `name` into the first one, appending their
+    /// properties and blocks in order, and removes the rest. For fusing split `connections` blocks.
+    /// Not any of the children's children though.
+    pub fn merge_children_named(&mut self, name: &str) {
+        let mut first = None;
+        let mut index = 0;
+        while index < self.blocks.len() {
+            if self.blocks[index].name.as_ref() != name {
+                index += 1;
+                continue;
+            }
+            let Some(first) = first else {
+                first = Some(index);
+                index += 1;
+                continue;
+            };
+            let merged = self.blocks.remove(index);
+            self.blocks[first].props.extend(merged.props);
+            self.blocks[first].blocks.extend(merged.blocks);
+        }
+    }
+
+    /// Returns the `entity` blocks of this block, including ones in `hidden` wrappers,
+    /// whose `origin` is within `radius` of `point`. Entities without a valid `origin`,
+    /// like brush entities, are skipped. Not any of the children's children though.
+    pub fn entities_near(&self, point: [f32; 3], radius: f32) -> Vec<&Block<S>> {
+        self.unwrap_hidden()
+            .filter(|block| block.name.as_ref() == "entity")
+            .filter(|entity| {
+                let Some(origin) = entity.props.iter().find(|prop| prop.key.as_ref() == "origin")
+                else {
+                    return false;
+                };
+                let Some([x, y, z]) = origin.value_vec3() else {
+                    return false;
+                };
+                let (dx, dy, dz) = (x - point[0], y - point[1], z - point[2]);
+                dx * dx + dy * dy + dz * dz <= radius * radius
+            })
+            .collect()
+    }
+
+    /// Returns every `entity` block in this block and all of its children, in the order
+    /// they appear, including ones in `hidden` wrappers. Does not include this block.
+    pub fn flatten_entities(&self) -> Vec<&Block<S>> {
+        self.iter_tree().skip(1).filter(|block| block.name.as_ref() == "entity").collect()
+    }
+
+    /// Follows child blocks by name, taking the first match at each level.
+    /// For example `["world", "solid", "side"]` returns the first side of the first solid
+    /// of the first world. An empty path returns `self`.
+    pub fn block_by_name_path(&self, path: &[&str]) -> Option<&Block<S>> {
+        path.iter().try_fold(self, |block, name| {
+            block.blocks.iter().find(|child| child.name.as_ref() == *name)
+        })
+    }
+
+    /// Iterates over the properties of this block that match the predicate.
+    /// Not any of the children's properties though.
+    pub fn properties_matching<'a>(
+        &'a self,
+        pred: impl Fn(&Property<S, S>) -> bool + 'a,
+    ) -> impl Iterator<Item = &'a Property<S, S>> {
+        self.props.iter().filter(move |prop| pred(prop))
+    }
+
+    /// Iterates over every property in this block and all of its children whose value
+    /// contains `needle`, along with the block it is in.
+    pub fn search_values<'a>(
+        &'a self,
+        needle: &'a str,
+    ) -> impl Iterator<Item = (&'a Block<S>, &'a Property<S, S>)> {
+        self.iter_tree().flat_map(move |block| {
+            block
+                .props
+                .iter()
+                .filter(move |prop| prop.value.as_ref().contains(needle))
+                .map(move |prop| (block, prop))
+        })
+    }
+
+    /// Iterates over the sub blocks of this block, yielding the blocks inside any
+    /// `hidden` wrappers as if they were direct children.
+    /// Hammer wraps hidden solids and entities in a `hidden` block.
+    pub fn unwrap_hidden(&self) -> impl Iterator<Item = &Block<S>> {
+        self.blocks.iter().flat_map(|block| {
+            if block.name.as_ref() == "hidden" {
+                block.blocks.iter()
+            } else {
+                std::slice::from_ref(block).iter()
+            }
+        })
+    }
+
+    /// Replaces every `hidden` wrapper in this block and all of its children with the blocks
+    /// inside it, unhiding them. Any properties of the wrappers are discarded.
+    pub fn flatten_hidden(&mut self) {
+        // loop in case of hidden directly inside hidden
+        while self.blocks.iter().any(|block| block.name.as_ref() == "hidden") {
+            let blocks = std::mem::take(&mut self.blocks);
+            for block in blocks {
+                if block.name.as_ref() == "hidden" {
+                    self.blocks.extend(block.blocks);
+                } else {
+                    self.blocks.push(block);
+                }
+            }
+        }
+        for block in self.blocks.iter_mut() {
+            block.flatten_hidden();
+        }
+    }
+
+    /// Returns the value of the first property with a matching key. When a key is repeated,
+    /// Hammer keeps the first like this and [`keep_first_duplicate_properties`](Self::keep_first_duplicate_properties),
+    /// while the engine keeps the last, see [`keep_last_duplicate_properties`](Self::keep_last_duplicate_properties).
+    pub fn get(&self, key: &str) -> Option<&S> {
+        self.props.iter().find(|prop| prop.key.as_ref() == key).map(|prop| &prop.value)
+    }
+
+    /// Returns the value of the first property with a matching key, see [`get`](Self::get).
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut S> {
+        self.props.iter_mut().find(|prop| prop.key.as_ref() == key).map(|prop| &mut prop.value)
+    }
+
+    /// Iterates over the values of every property with a matching key in order,
+    /// for keys that can be repeated.
+    pub fn get_all<'b>(&'b self, key: &'b str) -> impl Iterator<Item = &'b S> + 'b {
+        self.props.iter().filter(move |prop| prop.key.as_ref() == key).map(|prop| &prop.value)
+    }
+
+    /// Returns the first sub block with a matching name.
+    /// Not any of the children's children though, like [`iter_children`](Self::iter_children).
+    pub fn find_block(&self, name: &str) -> Option<&Block<S>> {
+        self.blocks.iter().find(|block| block.name.as_ref() == name)
+    }
+
+    /// Returns the first sub block with a matching name, see [`find_block`](Self::find_block).
+    pub fn find_block_mut(&mut self, name: &str) -> Option<&mut Block<S>> {
+        self.blocks.iter_mut().find(|block| block.name.as_ref() == name)
+    }
+
+    /// Iterates over every sub block with a matching name in order.
+    /// Not any of the children's children though, like [`iter_children`](Self::iter_children).
+    pub fn find_blocks<'b>(&'b self, name: &'b str) -> impl Iterator<Item = &'b Block<S>> + 'b {
+        self.blocks.iter().filter(move |block| block.name.as_ref() == name)
+    }
+
+    /// Iterates over every sub block with a matching name in order, see [`find_blocks`](Self::find_blocks).
+    pub fn find_blocks_mut<'b>(
+        &'b mut self,
+        name: &'b str,
+    ) -> impl Iterator<Item = &'b mut Block<S>> + 'b {
+        self.blocks.iter_mut().filter(move |block| block.name.as_ref() == name)
+    }
+
+    /// Returns the first property with a matching key, ignoring ASCII case.
+    /// Keys are case insensitive in-engine.
+    pub fn get_property_ci(&self, key: &str) -> Option<&Property<S, S>> {
+        self.props.iter().find(|prop| prop.key_eq_ignore_case(key))
+    }
+
+    /// Parses the value of the first property with a matching key as an `i32`.
+    /// Returns `None` if there is no such property or the value is not an integer.
+    pub fn get_i32(&self, key: &str) -> Option<i32> {
+        self.get(key)?.as_ref().trim().parse().ok()
+    }
+
+    /// Parses the value of the first property with a matching key as an `f32`.
+    /// Returns `None` if there is no such property or the value is not a float.
+    pub fn get_f32(&self, key: &str) -> Option<f32> {
+        self.get(key)?.as_ref().trim().parse().ok()
+    }
+
+    /// Parses the value of the `id` property, like [`get_i32`](Self::get_i32).
+    /// Returns `None` if it is missing or not an integer.
+    pub fn id(&self) -> Option<i32> {
+        self.get_i32("id")
+    }
+
+    /// Estimates the heap memory used by this block and all of its children, in bytes.
+    /// Counts the capacity of every `Vec` and the length of every name, key, and value,
+    /// even if they are borrowed. For comparing borrowed and owned parsing.
+    pub fn deep_size_bytes(&self) -> usize {
+        let vecs = self.props.capacity() * std::mem::size_of::<Property<S, S>>()
+            + self.blocks.capacity() * std::mem::size_of::<Block<S>>();
+        let strings = self.name.as_ref().len()
+            + self
+                .props
+                .iter()
+                .map(|prop| prop.key.as_ref().len() + prop.value.as_ref().len())
+                .sum::<usize>();
+        vecs + strings + self.blocks.iter().map(Block::deep_size_bytes).sum::<usize>()
+    }
+
+    /// Sums the byte lengths of every property key and value in this block and all
+    /// of its children. Useful as a rough estimate of the output size.
+    pub fn total_property_bytes(&self) -> usize {
+        let own: usize =
+            self.props.iter().map(|prop| prop.key.as_ref().len() + prop.value.as_ref().len()).sum();
+        own + self.blocks.iter().map(Block::total_property_bytes).sum::<usize>()
+    }
+}
+
+impl<S: AsRef<str>> Block<S> {
+    /// Checks if the trees are equal, skipping every `id` property in both.
+    /// For finding duplicate geometry across maps, where ids differ.
+    pub fn eq_ignore_ids(&self, other: &Block<S>) -> bool {
+        fn non_ids<S: AsRef<str>>(block: &Block<S>) -> impl Iterator<Item = (&str, &str)> {
+            block
+                .props
+                .iter()
+                .filter(|prop| !prop.is_id())
+                .map(|prop| (prop.key.as_ref(), prop.value.as_ref()))
+        }
+
+        self.name.as_ref() == other.name.as_ref()
+            && non_ids(self).eq(non_ids(other))
+            && self.blocks.len() == other.blocks.len()
+            && self.blocks.iter().zip(other.blocks.iter()).all(|(a, b)| a.eq_ignore_ids(b))
+    }
+
+    /// Hashes the tree, skipping every `id` property.
+    /// Consistent with [`Block::eq_ignore_ids`], blocks that are equal ignoring ids
+    /// have the same hash. Different blocks can have the same hash too, use
+    /// [`IgnoringIds`] as the key to deduplicate.
+    pub fn hash_ignoring_ids(&self) -> u64 {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        self.hash_ignoring_ids_into(&mut hasher);
+        hasher.finish()
+    }
+
+    fn hash_ignoring_ids_into(&self, state: &mut impl Hasher) {
+        self.name.as_ref().hash(state);
+        for prop in self.props.iter().filter(|prop| !prop.is_id()) {
+            prop.key.as_ref().hash(state);
+            prop.value.as_ref().hash(state);
+        }
+        // separate the properties from the blocks
+        self.blocks.len().hash(state);
+        for block in self.blocks.iter() {
+            block.hash_ignoring_ids_into(state);
+        }
+    }
+}
+
+/// A [`Block`] that hashes and compares skipping every `id` property, see
+/// [`Block::eq_ignore_ids`]. For keys in a `HashSet` or `HashMap` that deduplicate geometry.
+#[derive(Debug)]
+pub struct IgnoringIds<'a, S>(pub &'a Block<S>);
+
+impl<S> Clone for IgnoringIds<'_, S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S> Copy for IgnoringIds<'_, S> {}
+
+impl<S: AsRef<str>> PartialEq for IgnoringIds<'_, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.eq_ignore_ids(other.0)
+    }
+}
+
+impl<S: AsRef<str>> Eq for IgnoringIds<'_, S> {}
+
+impl<S: AsRef<str>> Hash for IgnoringIds<'_, S> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash_ignoring_ids_into(state);
+    }
+}
+
+impl<S: AsRef<str>> Block<S> {
+    /// Removes properties with the same key as an earlier one, so the first one wins,
+    /// like when Hammer loads a map and the same value [`get`](Self::get) returns.
+    /// Not any of the children's properties though.
+    pub fn keep_first_duplicate_properties(&mut self) {
+        let mut seen = HashSet::new();
+        let keep: Vec<bool> =
+            self.props.iter().map(|prop| seen.insert(prop.key.as_ref())).collect();
+        let mut keep = keep.into_iter();
+        self.props.retain(|_| keep.next().unwrap_or(true));
+    }
+
+    /// Removes properties with the same key as a later one, so the last one wins,
+    /// like when the engine reads entity keyvalues. [`get`](Self::get) returns the first
+    /// instead, see [`keep_first_duplicate_properties`](Self::keep_first_duplicate_properties).
+    /// Not any of the children's properties though.
+    pub fn keep_last_duplicate_properties(&mut self) {
+        let mut seen = HashSet::new();
+        let mut keep: Vec<bool> =
+            self.props.iter().rev().map(|prop| seen.insert(prop.key.as_ref())).collect();
+        keep.reverse();
+        let mut keep = keep.into_iter();
+        self.props.retain(|_| keep.next().unwrap_or(true));
+    }
+}
+
+impl<S: AsRef<str> + From<String>> Block<S> {
+    /// Replaces the name of this block and all of its children with the output of `f`,
+    /// depth first with parents before children.
+    pub fn map_names(&mut self, mut f: impl FnMut(&str) -> String) {
+        fn map<S: AsRef<str> + From<String>>(
+            block: &mut Block<S>,
+            f: &mut impl FnMut(&str) -> String,
+        ) {
+            block.name = f(block.name.as_ref()).into();
+            block.blocks.iter_mut().for_each(|child| map(child, f));
+        }
+        map(self, &mut f);
+    }
+
+    /// Moves all geometry in this block and all of its children by `offset`,
+    /// rewriting every `origin` and `plane` property.
+    /// Values that fail to parse are left as is, as are texture axes.
+    /// Uses `f64` so coordinates keep their precision, and a zero offset changes nothing.
+    pub fn translate(&mut self, offset: [f64; 3]) {
+        if offset == [0.0; 3] {
+            return;
+        }
+
+        for prop in self.props.iter_mut() {
+            let moved = match prop.key.as_ref() {
+                "origin" => parse_float_list(prop.value.as_ref())
+                    .and_then(|origin| <[f64; 3]>::try_from(origin).ok())
+                    .map(|origin| format_point(add(origin, offset))),
+                "plane" => parse_float_list(prop.value.as_ref())
+                    .and_then(|plane| <[f64; 9]>::try_from(plane).ok())
+                    .map(|plane| {
+                        let point = |i: usize| {
+                            format_point(add([plane[i], plane[i + 1], plane[i + 2]], offset))
+                        };
+                        format!("({}) ({}) ({})", point(0), point(3), point(6))
+                    }),
+                _ => None,
+            };
+            if let Some(moved) = moved {
+                prop.value = S::from(moved);
+            }
+        }
+
+        for block in self.blocks.iter_mut() {
+            block.translate(offset);
+        }
+    }
+}
+
+fn add(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+/// Formats a point like Hammer, `x y z`.
+fn format_point([x, y, z]: [f64; 3]) -> String {
+    format!("{x} {y} {z}")
+}
+
+/// Tolerance for comparing coordinates in [`Block::dedup_sides_by_plane`].
+const PLANE_TOLERANCE: f32 = 0.001;
+
+impl<S: AsRef<str>> Block<S> {
+    /// Counts the `side` blocks of this block. Not any of the children's children though.
+    pub fn side_count(&self) -> usize {
+        self.blocks.iter().filter(|block| block.name.as_ref() == "side").count()
+    }
+
+    /// Checks if this is a `solid` with at least 4 sides, the fewest a brush can have,
+    /// and every side has a valid `plane`.
+    pub fn is_valid_solid(&self) -> bool {
+        let has_plane = |side: &Block<S>| {
+            side.props.iter().any(|prop| {
+                prop.key.as_ref() == "plane" && parse_plane(prop.value.as_ref()).is_some()
+            })
+        };
+        self.name.as_ref() == "solid"
+            && self.side_count() >= 4
+            && self.blocks.iter().filter(|block| block.name.as_ref() == "side").all(has_plane)
+    }
+
+    /// Removes `side` blocks of this `solid` whose `plane` is the same as an earlier side's,
+    /// within a small tolerance. Duplicate planes are degenerate.
+    /// Sides without a valid `plane` are kept.
+    pub fn dedup_sides_by_plane(&mut self) {
+        let mut planes: Vec<[f32; 9]> = Vec::new();
+        self.blocks.retain(|block| {
+            if block.name.as_ref() != "side" {
+                return true;
+            }
+            let plane = block
+                .props
+                .iter()
+                .find(|prop| prop.key.as_ref() == "plane")
+                .and_then(|prop| parse_plane(prop.value.as_ref()));
+            let Some(plane) = plane else {
+                return true;
+            };
+
+            let is_duplicate = planes.iter().any(|other| {
+                other.iter().zip(plane.iter()).all(|(a, b)| (a - b).abs() <= PLANE_TOLERANCE)
+            });
+            if !is_duplicate {
+                planes.push(plane);
+            }
+            !is_duplicate
+        });
+    }
+}
+
+/// Parses a plane in the form `(x y z) (x y z) (x y z)`.
+fn parse_plane(value: &str) -> Option<[f32; 9]> {
+    parse_float_list(value)?.try_into().ok()
+}
+
+/// Parses whitespace separated floats, ignoring brackets and parentheses.
+/// Uses [`str::parse`], so `-1.5e3`, `+0.25`, and `.5` are all valid.
+fn parse_float_list<T: FromStr>(value: &str) -> Option<Vec<T>> {
+    value
+        .split(|c: char| matches!(c, '[' | ']' | '(' | ')') || c.is_whitespace())
+        .filter(|num| !num.is_empty())
+        .map(|num| num.parse().ok())
+        .collect()
+}
+
+impl<S: Ord> Block<S> {
+    /// Inserts a property keeping properties sorted by key, using a binary search.
+    /// Properties must already be sorted. Inserted after any properties with an equal key.
+    pub fn insert_property_sorted(&mut self, prop: Property<S, S>) {
+        let index = self.props.partition_point(|other| other.key <= prop.key);
+        self.props.insert(index, prop);
+    }
+}
+
+impl<S: AsRef<str> + std::fmt::Display> Block<S> {
+    /// Recursively sorts properties by key then value, and child blocks by name
+    /// then their [`Display`](std::fmt::Display) output, for a deterministic layout.
+    /// Equivalent trees will display identically after sorting.
+    pub fn sort_tree(&mut self) {
+        self.props.sort_by(|a, b| {
+            a.key.as_ref().cmp(b.key.as_ref()).then_with(|| a.value.as_ref().cmp(b.value.as_ref()))
+        });
+        for block in self.blocks.iter_mut() {
+            block.sort_tree();
+        }
+        // children are sorted first so their output is canonical
+        self.blocks
+            .sort_by_cached_key(|block| (block.name.as_ref().to_string(), block.to_string()));
+    }
+}
+
+impl<'a, S: AsRef<str> + From<&'a str>> Block<S> {
+    /// Returns the first property with a matching key,
+    /// or inserts a new one with the default value if there is none.
+    pub fn ensure_property(&mut self, key: &'a str, default: impl Into<S>) -> &mut Property<S, S> {
+        let index = match self.props.iter().position(|prop| prop.key.as_ref() == key) {
+            Some(index) => index,
+            None => {
+                self.props.push(Property::new(key, default));
+                self.props.len() - 1
+            }
+        };
+        &mut self.props[index]
+    }
 }
 
 impl<S, V> Property<S, V> {
@@ -77,12 +918,96 @@ impl<S, V> Property<S, V> {
 }
 
 impl<S: AsRef<str>, V> Property<S, V> {
+    /// Like [`Property::new`] but errors if the key contains a double quote,
+    /// which would produce invalid output when displayed.
+    pub fn try_new<T: Into<S>, U: Into<V>>(key: T, value: U) -> Result<Self, InvalidKeyError> {
+        let key = key.into();
+        if key.as_ref().contains('"') {
+            return Err(InvalidKeyError { key: key.as_ref().to_string() });
+        }
+        Ok(Self { key, value: value.into() })
+    }
+
+    /// Parses the value as a float, ignoring surrounding whitespace.
+    /// Accepts signs, exponents, and a missing leading zero like `-1.5e3`, `+0.25`, and `.5`.
+    pub fn value_f32(&self) -> Option<f32>
+    where
+        V: AsRef<str>,
+    {
+        self.value.as_ref().trim().parse().ok()
+    }
+
+    /// Parses the value as three whitespace separated floats, like `origin` values.
+    /// See [`Property::value_f32_list`] for the accepted forms.
+    pub fn value_vec3(&self) -> Option<[f32; 3]>
+    where
+        V: AsRef<str>,
+    {
+        parse_float_list(self.value.as_ref())?.try_into().ok()
+    }
+
+    /// Parses the value as a whitespace separated list of floats of any length.
+    /// Brackets and parentheses are ignored, so `uaxis` values like `[1 0 0 0] 0.25`
+    /// and `plane` values work too. Returns `None` if any item is not a float.
+    pub fn value_f32_list(&self) -> Option<Vec<f32>>
+    where
+        V: AsRef<str>,
+    {
+        parse_float_list(self.value.as_ref())
+    }
+
+    /// Splits the key on `.`, for custom keyvalues with dotted keys like `foo.bar`.
+    /// A key without dots is a single segment.
+    pub fn key_segments(&self) -> impl Iterator<Item = &str> {
+        self.key.as_ref().split('.')
+    }
+
+    /// Checks if the key is equal to `other`, ignoring ASCII case.
+    pub fn key_eq_ignore_case(&self, other: &str) -> bool {
+        self.key.as_ref().eq_ignore_ascii_case(other)
+    }
+
     /// Checks if the key is "id".
     pub fn is_id(&self) -> bool {
         self.key.as_ref() == "id"
     }
 }
 
+/// Options for [`Vmf::merge`]. The default keeps every block as is.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MergeOptions {
+    /// Fuse `world` blocks into one, concatenating their solids and other sub blocks.
+    pub fuse_worlds: bool,
+}
+
+/// Error for a [`Property`] key that cannot be displayed, see [`Property::try_new`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct InvalidKeyError {
+    pub key: String,
+}
+
+impl std::fmt::Display for InvalidKeyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid property key {:?}, keys cannot contain '\"'", self.key)
+    }
+}
+
+impl std::error::Error for InvalidKeyError {}
+
+/// Error for a [`Block`] name that cannot be displayed, see [`Block::try_set_name`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct InvalidNameError {
+    pub name: String,
+}
+
+impl std::fmt::Display for InvalidNameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid block name {:?}, names must be ASCII alphanumerics or '_'", self.name)
+    }
+}
+
+impl std::error::Error for InvalidNameError {}
+
 // Trait impls
 
 impl<'a, S: From<&'a str>> Default for Vmf<S> {
@@ -122,3 +1047,1014 @@ impl<S> From<Vmf<S>> for Block<S> {
         vmf.inner
     }
 }
+
+impl<S> From<Block<S>> for Vmf<S> {
+    /// Uses the block as the root, keeping its name and properties.
+    /// The inverse of `From<Vmf<S>> for Block<S>`.
+    fn from(block: Block<S>) -> Self {
+        Self { inner: block }
+    }
+}
+
+impl<S> IntoIterator for Vmf<S> {
+    type Item = Block<S>;
+    type IntoIter = std::vec::IntoIter<Block<S>>;
+
+    /// Consumes the `Vmf` into its root blocks.
+    fn into_iter(self) -> Self::IntoIter {
+        self.inner.blocks.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ensure_property() {
+        let mut block: Block<String> =
+            Block::new("entity", vec![Property::new("classname", "info_target")], vec![]);
+
+        let prop = block.ensure_property("classname", "default");
+        assert_eq!(prop.value, "info_target");
+        prop.value = "info_null".to_string();
+
+        let prop = block.ensure_property("targetname", "target_1");
+        assert_eq!(*prop, Property::new("targetname", "target_1"));
+
+        assert_eq!(
+            block.props,
+            vec![Property::new("classname", "info_null"), Property::new("targetname", "target_1")]
+        );
+    }
+
+    #[test]
+    fn total_property_bytes() {
+        let block: Block<&str> = Block::new(
+            "world",
+            vec![Property::new("id", "1"), Property::new("skyname", "sky_day01_01")],
+            vec![Block::new(
+                "solid",
+                vec![Property::new("id", "2")],
+                vec![Block::new(
+                    "side",
+                    vec![Property::new("material", "DEV/DEV_MEASUREWALL01A")],
+                    vec![],
+                )],
+            )],
+        );
+        // "id" "1" + "skyname" "sky_day01_01" + "id" "2" + "material" "DEV/DEV_MEASUREWALL01A"
+        assert_eq!(block.total_property_bytes(), 3 + 19 + 3 + 30);
+        assert_eq!(Block::<&str>::new("empty", vec![], vec![]).total_property_bytes(), 0);
+    }
+
+    #[test]
+    fn vmf_into_iter() {
+        let vmf = crate::parse::<String, ()>("world{}entity{\"id\" \"1\"}entity{}").unwrap();
+        let blocks: Vec<Block<String>> = vmf.into_iter().collect();
+        assert_eq!(
+            blocks,
+            vec![
+                Block::new("world", vec![], vec![]),
+                Block::new("entity", vec![Property::new("id", "1")], vec![]),
+                Block::new("entity", vec![], vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn get_property_ci() {
+        let block: Block<&str> = Block::new(
+            "entity",
+            vec![Property::new("ClassName", "info_target"), Property::new("classname", "second")],
+            vec![],
+        );
+        assert_eq!(
+            block.get_property_ci("classname"),
+            Some(&Property::new("ClassName", "info_target"))
+        );
+        assert_eq!(block.get_property_ci("CLASSNAME").map(|prop| prop.value), Some("info_target"));
+        assert_eq!(block.get_property_ci("targetname"), None);
+    }
+
+    #[test]
+    fn sort_tree() {
+        let mut a = crate::parse::<&str, ()>(
+            r#"world { "b" "2" "a" "1" solid { side { "y" "0" } side { "x" "0" } } solid { "id" "1" } }
+            entity { "classname" "light" }"#,
+        )
+        .unwrap();
+        let mut b = crate::parse::<&str, ()>(
+            r#"entity { "classname" "light" }
+            world { solid { "id" "1" } "a" "1" solid { side { "x" "0" } side { "y" "0" } } "b" "2" }"#,
+        )
+        .unwrap();
+        assert_ne!(a.to_string(), b.to_string());
+
+        a.sort_tree();
+        b.sort_tree();
+        assert_eq!(a.to_string(), b.to_string());
+        assert_eq!(a, b);
+        assert_eq!(a.blocks[0].name, "entity");
+        assert_eq!(a.blocks[1].props[0].key, "a");
+    }
+
+    #[test]
+    fn property_try_new() {
+        let prop = Property::<&str, &str>::try_new("targetname", "door_1").unwrap();
+        assert_eq!(prop, Property::new("targetname", "door_1"));
+
+        let err = Property::<String, String>::try_new("target\"name", "door_1").unwrap_err();
+        assert_eq!(err, InvalidKeyError { key: "target\"name".to_string() });
+        assert!(Property::<&str, &str>::try_new("\"", "").is_err());
+        // values are not checked
+        assert!(Property::<&str, &str>::try_new("key", "\"").is_ok());
+    }
+
+    #[test]
+    fn block_by_name_path() {
+        let vmf = crate::parse::<&str, ()>(
+            r#"versioninfo{} world { "id" "1" solid { "id" "2" side { "id" "3" } side { "id" "4" } } }"#,
+        )
+        .unwrap();
+
+        let side = vmf.block_by_name_path(&["world", "solid", "side"]).unwrap();
+        assert_eq!(side.props, vec![Property::new("id", "3")]);
+        assert_eq!(vmf.block_by_name_path(&[]), Some(vmf.root()));
+
+        assert_eq!(vmf.block_by_name_path(&["world", "entity", "side"]), None);
+        assert_eq!(vmf.block_by_name_path(&["world", "solid", "side", "side"]), None);
+    }
+
+    #[test]
+    fn properties_matching() {
+        let block: Block<&str> = Block::new(
+            "light",
+            vec![
+                Property::new("classname", "light"),
+                Property::new("_fifty_percent_distance", "0.5"),
+                Property::new("_light", "255 255 255 200"),
+                Property::new("_quadratic_attn", "1"),
+                Property::new("_zero_percent_distance", ""),
+            ],
+            vec![],
+        );
+
+        let floats: Vec<_> =
+            block.properties_matching(|prop| prop.value.parse::<f32>().is_ok()).collect();
+        assert_eq!(
+            floats,
+            vec![
+                &Property::new("_fifty_percent_distance", "0.5"),
+                &Property::new("_quadratic_attn", "1")
+            ]
+        );
+        assert_eq!(block.properties_matching(|prop| prop.value.is_empty()).count(), 1);
+    }
+
+    #[test]
+    fn remove_empty_entities() {
+        let mut vmf = crate::parse::<&str, ()>(
+            r#"world {} entity {} entity { "classname" "light" } entity { editor {} } entity {}"#,
+        )
+        .unwrap();
+        vmf.remove_empty_entities();
+
+        let truth = crate::parse::<&str, ()>(
+            r#"world {} entity { "classname" "light" } entity { editor {} }"#,
+        )
+        .unwrap();
+        assert_eq!(vmf, truth);
+    }
+
+    #[test]
+    fn iter_children_rev() {
+        let vmf = crate::parse::<&str, ()>("a{} b{ d{} } c{}").unwrap();
+        let names: Vec<_> = vmf.iter_children_rev().map(|block| block.name).collect();
+        assert_eq!(names, vec!["c", "b", "a"]);
+
+        let names: Vec<_> = vmf.iter_children_rev().rev().map(|block| block.name).collect();
+        assert_eq!(names, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn num_properties_blocks() {
+        let vmf = crate::parse::<&str, ()>(r#"a{ "k" "v" "k2" "v2" b{ "k" "v" } c{} }"#).unwrap();
+        assert_eq!(vmf.num_properties(), 0);
+        assert_eq!(vmf.num_blocks(), 1);
+        assert_eq!(vmf.blocks[0].num_properties(), 2);
+        assert_eq!(vmf.blocks[0].num_blocks(), 2);
+        assert_eq!(vmf.blocks[0].blocks[1].num_blocks(), 0);
+    }
+
+    #[test]
+    fn take_properties_blocks() {
+        let mut vmf = crate::parse::<&str, ()>(r#"a{ "k" "v" b{} c{} }"#).unwrap();
+        let a = &mut vmf.blocks[0];
+
+        assert_eq!(a.take_properties(), vec![Property::new("k", "v")]);
+        assert!(a.props.is_empty());
+        assert_eq!(a.take_properties(), vec![]);
+
+        let blocks = a.take_blocks();
+        assert_eq!(blocks, vec![Block::new("b", vec![], vec![]), Block::new("c", vec![], vec![])]);
+        assert!(a.blocks.is_empty());
+        assert_eq!(*a, Block::new("a", vec![], vec![]));
+    }
+
+    #[test]
+    fn search_values() {
+        let vmf = crate::parse::<&str, ()>(
+            r#"world { "skyname" "sky_day01_01"
+                solid { side { "material" "BRICK/BRICKWALL001" } side { "material" "TOOLS/TOOLSNODRAW" } }
+                solid { side { "material" "brick/brickwall002" } } }
+            entity { "classname" "prop_static" "model" "models/brick.mdl" }"#,
+        )
+        .unwrap();
+
+        let found: Vec<_> =
+            vmf.search_values("BRICK").map(|(block, prop)| (block.name, prop.value)).collect();
+        assert_eq!(found, vec![("side", "BRICK/BRICKWALL001")]);
+
+        let found: Vec<_> = vmf.search_values("brick").map(|(_, prop)| prop.value).collect();
+        assert_eq!(found, vec!["brick/brickwall002", "models/brick.mdl"]);
+        assert_eq!(vmf.search_values("missing").count(), 0);
+    }
+
+    #[test]
+    fn insert_property_sorted() {
+        let mut block: Block<&str> = Block::new("entity", vec![], vec![]);
+        fn keys<'a>(block: &Block<&'a str>) -> Vec<&'a str> {
+            block.props.iter().map(|prop| prop.key).collect()
+        }
+
+        // empty
+        block.insert_property_sorted(Property::new("m", "1"));
+        assert_eq!(keys(&block), vec!["m"]);
+        // front
+        block.insert_property_sorted(Property::new("a", "2"));
+        assert_eq!(keys(&block), vec!["a", "m"]);
+        // end
+        block.insert_property_sorted(Property::new("z", "3"));
+        assert_eq!(keys(&block), vec!["a", "m", "z"]);
+        // middle
+        block.insert_property_sorted(Property::new("n", "4"));
+        assert_eq!(keys(&block), vec!["a", "m", "n", "z"]);
+        // after equal key
+        block.insert_property_sorted(Property::new("m", "5"));
+        assert_eq!(block.props[2], Property::new("m", "5"));
+    }
+
+    #[test]
+    fn hidden() {
+        let input = r#"world {
+                solid { "id" "1" }
+                hidden { solid { "id" "2" } solid { "id" "3" } }
+                solid { "id" "4" }
+            }
+            hidden { entity { "id" "5" hidden { editor {} } } }
+            entity { "id" "6" }"#;
+        let mut vmf = crate::parse::<&str, ()>(input).unwrap();
+
+        let ids: Vec<_> = vmf.blocks[0].unwrap_hidden().map(|block| block.props[0].value).collect();
+        assert_eq!(ids, vec!["1", "2", "3", "4"]);
+        let names: Vec<_> = vmf.unwrap_hidden().map(|block| block.name).collect();
+        assert_eq!(names, vec!["world", "entity", "entity"]);
+
+        vmf.flatten_hidden();
+        let truth = crate::parse::<&str, ()>(
+            r#"world { solid { "id" "1" } solid { "id" "2" } solid { "id" "3" } solid { "id" "4" } }
+            entity { "id" "5" editor {} }
+            entity { "id" "6" }"#,
+        )
+        .unwrap();
+        assert_eq!(vmf, truth);
+    }
+
+    #[test]
+    fn key_eq_ignore_case() {
+        let prop = Property::<&str, &str>::new("TargetName", "door_1");
+        assert!(prop.key_eq_ignore_case("targetname"));
+        assert!(prop.key_eq_ignore_case("TARGETNAME"));
+        assert!(prop.key_eq_ignore_case("TargetName"));
+        assert!(!prop.key_eq_ignore_case("target_name"));
+        assert!(!prop.key_eq_ignore_case("door_1"));
+    }
+
+    #[test]
+    fn reindex_entity_ids() {
+        let mut vmf = crate::parse::<String, ()>(
+            r#"world { "id" "1" solid { "id" "2" side { "id" "3" } } }
+            entity { "id" "40" "classname" "func_detail" solid { "id" "5" side { "id" "6" } } }
+            entity { "classname" "light" }
+            hidden { entity { "id" "7" } }"#,
+        )
+        .unwrap();
+        vmf.reindex_entity_ids(100);
+
+        let truth = crate::parse::<String, ()>(
+            r#"world { "id" "1" solid { "id" "2" side { "id" "3" } } }
+            entity { "id" "100" "classname" "func_detail" solid { "id" "5" side { "id" "6" } } }
+            entity { "id" "101" "classname" "light" }
+            hidden { entity { "id" "102" } }"#,
+        )
+        .unwrap();
+        assert_eq!(vmf, truth);
+    }
+
+    #[test]
+    fn vmf_macro() {
+        let vmf: Vmf<&str> = crate::vmf! {
+            versioninfo { "editorversion" "400"; }
+            world {
+                "id" "1";
+                "classname" "worldspawn";
+                solid {
+                    "id" "2";
+                    side { "id" "3"; "plane" "(0 0 0) (0 1 0) (1 1 0)"; }
+                    side {}
+                }
+            }
+            entity { "classname" "light"; }
+        };
+        let input = r#"versioninfo { "editorversion" "400" }
+            world { "id" "1" "classname" "worldspawn"
+                solid { "id" "2" side { "id" "3" "plane" "(0 0 0) (0 1 0) (1 1 0)" } side {} } }
+            entity { "classname" "light" }"#;
+        assert_eq!(vmf, crate::parse::<&str, ()>(input).unwrap());
+
+        let empty: Vmf<String> = crate::vmf! {};
+        assert_eq!(empty, Vmf::default());
+
+        let id = 42.to_string();
+        let block: Block<String> = crate::block!(side { "id" id; });
+        assert_eq!(block, Block::new("side", vec![Property::new("id", "42")], vec![]));
+    }
+
+    #[test]
+    fn count_matching() {
+        let vmf = crate::parse::<&str, ()>(
+            r#"world {
+                solid { side {} side {} side {} side {} side {} side {} }
+                solid { side {} side {} side {} side {} side {} }
+            }
+            entity { solid { side {} side {} side {} side {} } }"#,
+        )
+        .unwrap();
+
+        assert_eq!(vmf.count_matching(|block| block.name == "side"), 15);
+        assert_eq!(vmf.count_matching(|block| block.name == "solid"), 3);
+        assert_eq!(vmf.count_matching(|_| true), 2 + 3 + 15);
+        assert_eq!(vmf.blocks[1].count_matching(|block| block.name == "entity"), 0);
+    }
+
+    #[test]
+    fn extract() {
+        let vmf = crate::parse::<&str, ()>(
+            r#"versioninfo {}
+            world { "id" "1" solid { "id" "2" } solid { "id" "3" side { "id" "4" } } }"#,
+        )
+        .unwrap();
+
+        let solid = vmf.extract(&[1, 1]).unwrap();
+        assert_eq!(
+            solid,
+            crate::parse::<&str, ()>(r#"solid { "id" "3" side { "id" "4" } }"#).unwrap()
+        );
+        assert_eq!(vmf.extract(&[]), Some(vmf.clone()));
+        assert_eq!(vmf.extract(&[1, 2]), None);
+        assert_eq!(vmf.extract(&[0, 0]), None);
+    }
+
+    #[test]
+    fn dedup_sides_by_plane() {
+        let mut solid = crate::parse::<&str, ()>(
+            r#"solid {
+                "id" "1"
+                side { "id" "1" "plane" "(0 0 0) (0 64 0) (64 64 0)" }
+                side { "id" "2" "plane" "(0 0 64) (64 0 64) (64 64 64)" }
+                side { "id" "3" "plane" "(0 0 0) (0 64 0) (64 64 0.0001)" }
+                side { "id" "4" "plane" "invalid" }
+                side { "id" "5" "plane" "invalid" }
+                editor { "plane" "(0 0 0) (0 64 0) (64 64 0)" }
+                side { "id" "6" "plane" "(0 0 64) (64 0 64) (64 64 64)" }
+            }"#,
+        )
+        .unwrap()
+        .into_iter()
+        .next()
+        .unwrap();
+        solid.dedup_sides_by_plane();
+
+        let ids: Vec<_> = solid.blocks.iter().filter_map(|block| block.props.first()).collect();
+        assert_eq!(
+            ids,
+            vec![
+                &Property::new("id", "1"),
+                &Property::new("id", "2"),
+                &Property::new("id", "4"),
+                &Property::new("id", "5"),
+                &Property::new("plane", "(0 0 0) (0 64 0) (64 64 0)"),
+            ]
+        );
+    }
+
+    #[test]
+    fn block_vmf_conversion() {
+        let block: Block<&str> = crate::block!(root { world { "id" "1"; } entity {} });
+        let vmf = Vmf::from(block.clone());
+        assert_eq!(vmf, crate::parse::<&str, ()>(r#"world { "id" "1" } entity {}"#).unwrap());
+        assert_eq!(Block::from(vmf), block);
+    }
+
+    #[test]
+    fn into_owned() {
+        let input = String::from(r#"world { "id" "1" solid { side { "plane" "(0 0 0)" } } }"#);
+        let owned: Vmf<String> = {
+            let borrowed = crate::parse::<&str, ()>(&input).unwrap();
+            borrowed.into_owned()
+        };
+        drop(input);
+        assert_eq!(owned.blocks[0].blocks[0].blocks[0].props[0].value, "(0 0 0)");
+        assert_eq!(owned.name, Vmf::<String>::ROOT_NAME);
+    }
+
+    #[test]
+    fn value_f32_list() {
+        let list = |value| Property::<&str, &str>::new("key", value).value_f32_list();
+
+        assert_eq!(list("0.5 -2"), Some(vec![0.5, -2.0]));
+        assert_eq!(list("  1 2\t3 "), Some(vec![1.0, 2.0, 3.0]));
+        assert_eq!(list("255 255 255 200"), Some(vec![255.0, 255.0, 255.0, 200.0]));
+        assert_eq!(list("[1 0 0 0] 0.25"), Some(vec![1.0, 0.0, 0.0, 0.0, 0.25]));
+        assert_eq!(list(""), Some(vec![]));
+
+        assert_eq!(list("1 2 three"), None);
+        assert_eq!(list("1,2,3"), None);
+    }
+
+    #[test]
+    fn get_numbers() {
+        let block: Block<&str> = crate::block!(entity {
+            "id" "12";
+            "angle" "-90.5";
+            "spawnflags" "not a number";
+        });
+        assert_eq!(block.get_i32("id"), Some(12));
+        assert_eq!(block.get_f32("angle"), Some(-90.5));
+        assert_eq!(block.get_f32("id"), Some(12.0));
+
+        assert_eq!(block.get_i32("angle"), None);
+        assert_eq!(block.get_i32("spawnflags"), None);
+        assert_eq!(block.get_f32("spawnflags"), None);
+
+        assert_eq!(block.get_i32("origin"), None);
+        assert_eq!(block.get_f32("origin"), None);
+    }
+
+    #[test]
+    fn solids() {
+        let vmf = crate::parse::<&str, ()>(
+            r#"world {
+                solid { "id" "1" side {} }
+                hidden { solid { "id" "2" } }
+            }
+            entity {
+                "classname" "func_detail"
+                solid { "id" "3" }
+                editor {}
+            }
+            entity { "classname" "light" }"#,
+        )
+        .unwrap();
+        let ids: Vec<_> = vmf.solids().map(|solid| solid.props[0].value).collect();
+        assert_eq!(ids, vec!["1", "2", "3"]);
+    }
+
+    #[test]
+    fn hash_ignoring_ids() {
+        use std::collections::HashSet;
+
+        let vmf = crate::parse::<&str, ()>(
+            r#"solid { "id" "1" side { "id" "1" "plane" "(0 0 0) (1 0 0) (0 1 0)" } }
+            solid { "id" "7" side { "id" "20" "plane" "(0 0 0) (1 0 0) (0 1 0)" } }
+            solid { "id" "8" side { "id" "21" "plane" "(0 0 1) (1 0 1) (0 1 1)" } }"#,
+        )
+        .unwrap();
+        let (a, b, c) = (&vmf.blocks[0], &vmf.blocks[1], &vmf.blocks[2]);
+        assert!(a.eq_ignore_ids(b));
+        assert!(!a.eq_ignore_ids(c));
+        assert_ne!(a, b);
+
+        let set: HashSet<_> = [a, b, c].into_iter().map(IgnoringIds).collect();
+        assert_eq!(set.len(), 2);
+        assert!(set.contains(&IgnoringIds(a)));
+        assert!(set.contains(&IgnoringIds(c)));
+        assert_eq!(a.hash_ignoring_ids(), b.hash_ignoring_ids());
+        assert_ne!(a.hash_ignoring_ids(), c.hash_ignoring_ids());
+    }
+
+    #[test]
+    fn walk() {
+        let vmf = crate::parse::<&str, ()>(
+            r#"world { solid { side {} } entity { "targetname" "door" } } entity {}"#,
+        )
+        .unwrap();
+
+        let mut visited = Vec::new();
+        let found = vmf.walk(|block| {
+            visited.push(block.name);
+            match block.props.first() {
+                Some(prop) => ControlFlow::Break(prop.value),
+                None => ControlFlow::Continue(()),
+            }
+        });
+        assert_eq!(found, ControlFlow::Break("door"));
+        // stopped before the last entity
+        assert_eq!(visited, vec!["root", "world", "solid", "side", "entity"]);
+
+        let mut count = 0;
+        let result: ControlFlow<()> = vmf.walk(|_| {
+            count += 1;
+            ControlFlow::Continue(())
+        });
+        assert_eq!((result, count), (ControlFlow::Continue(()), 6));
+    }
+
+    #[test]
+    fn numeric_forms() {
+        let prop = |value| Property::<&str, &str>::new("key", value);
+
+        for (value, float) in [("-1.5e3", -1500.0), ("+0.25", 0.25), (".5", 0.5), ("-.5E-1", -0.05)]
+        {
+            assert_eq!(prop(value).value_f32(), Some(float), "{value}");
+            let block: Block<&str> = Block::new("entity", vec![prop(value)], vec![]);
+            assert_eq!(block.get_f32("key"), Some(float), "{value}");
+        }
+        assert_eq!(prop(" 2 ").value_f32(), Some(2.0));
+        assert_eq!(prop("1.5.0").value_f32(), None);
+        assert_eq!(prop("e3").value_f32(), None);
+
+        assert_eq!(prop("-1.5e3 +0.25 .5").value_vec3(), Some([-1500.0, 0.25, 0.5]));
+        assert_eq!(prop("1 2").value_vec3(), None);
+        assert_eq!(prop("1 2 3 4").value_vec3(), None);
+
+        assert_eq!(
+            parse_plane("(-1.5e3 +0.25 .5) (0 0 0) (1e1 -1 +1)"),
+            Some([-1500.0, 0.25, 0.5, 0.0, 0.0, 0.0, 10.0, -1.0, 1.0])
+        );
+        assert_eq!(parse_plane("(1 2 3) (4 5 6) (7 8 --9)"), None);
+    }
+
+    #[test]
+    fn into_block_map() {
+        let vmf = crate::parse::<&str, ()>(
+            r#"versioninfo {} world { "id" "1" } entity { "id" "2" } entity { "id" "3" } cameras {}"#,
+        )
+        .unwrap();
+        let map = vmf.into_block_map();
+        assert_eq!(map.len(), 4);
+        assert_eq!(map["world"].len(), 1);
+        let ids: Vec<_> = map["entity"].iter().map(|entity| entity.props[0].value).collect();
+        assert_eq!(ids, vec!["2", "3"]);
+        assert!(!map.contains_key("cordon"));
+    }
+
+    #[test]
+    fn set_name() {
+        let mut block: Block<String> = Block::new("entity", vec![], vec![]);
+        block.set_name("anything goes");
+        assert_eq!(block.name, "anything goes");
+
+        assert_eq!(block.try_set_name("func_detail_2"), Ok(()));
+        assert_eq!(block.name, "func_detail_2");
+
+        for name in ["", "two words", "tab\t", "quote\"", "brace{", "brace}"] {
+            assert_eq!(
+                block.try_set_name(name),
+                Err(InvalidNameError { name: name.to_string() }),
+                "{name:?}"
+            );
+        }
+        assert_eq!(block.name, "func_detail_2");
+    }
+
+    #[test]
+    fn flatten_entities() {
+        let vmf = crate::parse::<&str, ()>(
+            r#"world { solid {} }
+            entity { "id" "1" entity { "id" "2" } }
+            hidden { entity { "id" "3" } }
+            group { entity { "id" "4" } }
+            entity { "id" "5" }"#,
+        )
+        .unwrap();
+        let ids: Vec<_> =
+            vmf.flatten_entities().iter().map(|entity| entity.props[0].value).collect();
+        assert_eq!(ids, vec!["1", "2", "3", "4", "5"]);
+
+        // not including self
+        assert_eq!(vmf.blocks[1].flatten_entities().len(), 1);
+    }
+
+    #[test]
+    fn merge_worlds() {
+        let first = crate::parse::<&str, ()>(
+            r#"world { "id" "1" "skyname" "sky_day01_01" solid { "id" "1" } } entity { "id" "2" }"#,
+        )
+        .unwrap();
+        let second = crate::parse::<&str, ()>(
+            r#"versioninfo {} world { "id" "1" "skyname" "other" solid { "id" "5" } solid { "id" "6" } }"#,
+        )
+        .unwrap();
+
+        let mut kept = first.clone();
+        kept.merge(second.clone(), &MergeOptions::default());
+        let names: Vec<_> = kept.blocks.iter().map(|block| block.name).collect();
+        assert_eq!(names, vec!["world", "entity", "versioninfo", "world"]);
+
+        let mut fused = first;
+        fused.merge(second, &MergeOptions { fuse_worlds: true });
+        let names: Vec<_> = fused.blocks.iter().map(|block| block.name).collect();
+        assert_eq!(names, vec!["world", "entity", "versioninfo"]);
+
+        let world = &fused.blocks[0];
+        assert_eq!(world.get_property_ci("skyname").unwrap().value, "sky_day01_01");
+        let solids: Vec<_> = world.blocks.iter().map(|solid| solid.props[0].value).collect();
+        assert_eq!(solids, vec!["1", "5", "6"]);
+
+        // nothing to fuse with
+        let mut empty = Vmf::<&str>::default();
+        empty.merge(fused.clone(), &MergeOptions { fuse_worlds: true });
+        assert_eq!(empty, fused);
+    }
+
+    #[test]
+    fn key_segments() {
+        let dotted = Property::<&str, &str>::new("foo.bar.baz", "1");
+        assert_eq!(dotted.key_segments().collect::<Vec<_>>(), vec!["foo", "bar", "baz"]);
+
+        let plain = Property::<&str, &str>::new("targetname", "door");
+        assert_eq!(plain.key_segments().collect::<Vec<_>>(), vec!["targetname"]);
+    }
+
+    #[test]
+    fn stats_report() {
+        let vmf = crate::parse::<&str, ()>(
+            r#"versioninfo {}
+            world { solid { side {} side {} side {} side {} } solid { side {} side {} side {} side {} } }
+            entity { "classname" "light" }
+            entity { "classname" "func_detail" solid { side {} side {} side {} side {} editor {} } }
+            hidden { entity { "classname" "light" } }
+            entity { "id" "9" }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            vmf.stats_report(),
+            "solids: 3\nsides: 12\nentities: 4\n\t<none>: 1\n\tfunc_detail: 1\n\tlight: 2\n"
+        );
+    }
+
+    #[test]
+    fn with_properties_blocks() {
+        let side = Block::new("side", vec![Property::new("id", "1")], vec![]);
+        let solid: Block<&str> = Block::new("solid", vec![Property::new("id", "old")], vec![])
+            .with_properties(vec![Property::new("id", "2")])
+            .with_blocks(vec![side.clone(), side]);
+
+        assert_eq!(solid, crate::block!(solid { "id" "2"; side { "id" "1"; } side { "id" "1"; } }));
+    }
+
+    #[test]
+    fn duplicate_properties() {
+        let block: Block<&str> = crate::block!(entity {
+            "targetname" "first";
+            "classname" "light";
+            "targetname" "second";
+            "_light" "255 255 255 200";
+            "targetname" "third";
+        });
+
+        let mut first = block.clone();
+        first.keep_first_duplicate_properties();
+        assert_eq!(
+            first.props,
+            vec![
+                Property::new("targetname", "first"),
+                Property::new("classname", "light"),
+                Property::new("_light", "255 255 255 200"),
+            ]
+        );
+
+        let mut last = block;
+        last.keep_last_duplicate_properties();
+        assert_eq!(
+            last.props,
+            vec![
+                Property::new("classname", "light"),
+                Property::new("_light", "255 255 255 200"),
+                Property::new("targetname", "third"),
+            ]
+        );
+    }
+
+    #[test]
+    fn unique_materials() {
+        let vmf = crate::parse::<&str, ()>(
+            r#"world {
+                solid {
+                    side { "material" "DEV/DEV_MEASUREWALL01A" }
+                    side { "material" "TOOLS/TOOLSNODRAW" }
+                    side { "material" "DEV/DEV_MEASUREWALL01A" }
+                }
+            }
+            entity { "material" "NOT/A_SIDE" solid { side { "material" "TOOLS/TOOLSNODRAW" } side { "material" "BRICK/BRICKWALL001A" } } }"#,
+        )
+        .unwrap();
+        let materials = vmf.unique_materials();
+        assert_eq!(materials.len(), 3);
+        assert!(materials.contains("DEV/DEV_MEASUREWALL01A"));
+        assert!(materials.contains("TOOLS/TOOLSNODRAW"));
+        assert!(materials.contains("BRICK/BRICKWALL001A"));
+    }
+
+    #[test]
+    fn translate() {
+        let mut vmf = crate::parse::<String, ()>(
+            r#"entity {
+                "classname" "light"
+                "origin" "-64 128 0.5"
+                "angles" "0 90 0"
+            }
+            world {
+                solid { side { "plane" "(-64 -64 64) (64 -64 64) (64 -64 -64)" "uaxis" "[1 0 0 0] 0.25" } }
+            }
+            entity { "origin" "not a point" }"#,
+        )
+        .unwrap();
+        vmf.translate([64.0, -128.0, 0.25]);
+
+        let entity = &vmf.blocks[0];
+        assert_eq!(entity.get_property_ci("origin").unwrap().value, "0 0 0.75");
+        assert_eq!(entity.get_property_ci("angles").unwrap().value, "0 90 0");
+
+        let side = vmf.block_by_name_path(&["world", "solid", "side"]).unwrap();
+        assert_eq!(side.props[0].value, "(0 -192 64.25) (128 -192 64.25) (128 -192 -63.75)");
+        assert_eq!(side.props[1].value, "[1 0 0 0] 0.25");
+
+        assert_eq!(vmf.blocks[2].props[0].value, "not a point");
+
+        // not representable as f32
+        let mut vmf = crate::parse::<String, ()>(r#"entity { "origin" "1234.5678 0 0" }"#).unwrap();
+        vmf.translate([0.0; 3]);
+        assert_eq!(vmf.blocks[0].props[0].value, "1234.5678 0 0");
+        vmf.translate([1.0, 0.0, 0.0]);
+        assert_eq!(vmf.blocks[0].props[0].value, "1235.5678 0 0");
+    }
+
+    #[test]
+    fn deep_size_bytes() {
+        let input = r#"world { "id" "1" "skyname" "sky_day01_01" solid { side {} side {} } }"#;
+        let vmf = crate::parse::<String, ()>(input).unwrap();
+
+        let strings = vmf.total_property_bytes() + "rootworldsolidsideside".len();
+        let vecs = 2 * std::mem::size_of::<Property<String, String>>()
+            + 4 * std::mem::size_of::<Block<String>>();
+        let size = vmf.deep_size_bytes();
+        assert!(size >= strings + vecs, "{size}");
+        // allow for some over allocation
+        assert!(size <= strings + 4 * vecs, "{size}");
+
+        let borrowed = crate::parse::<&str, ()>(input).unwrap();
+        assert!(borrowed.deep_size_bytes() < size);
+    }
+
+    #[test]
+    fn merge_children_named() {
+        let mut entity: Block<&str> = crate::block!(entity {
+            "classname" "logic_relay";
+            connections { "OnTrigger" "a,Open,,0,-1"; }
+            editor { "color" "220 30 220"; }
+            connections { "OnSpawn" "b,Close,,0,-1"; }
+        });
+        entity.merge_children_named("connections");
+
+        assert_eq!(
+            entity,
+            crate::block!(entity {
+                "classname" "logic_relay";
+                connections { "OnTrigger" "a,Open,,0,-1"; "OnSpawn" "b,Close,,0,-1"; }
+                editor { "color" "220 30 220"; }
+            })
+        );
+        assert_eq!(entity.entity_connections().len(), 2);
+
+        let unchanged = entity.clone();
+        entity.merge_children_named("solid");
+        assert_eq!(entity, unchanged);
+    }
+
+    #[test]
+    fn is_valid_solid() {
+        let side = |plane| crate::block!(side { "id" "1"; "plane" plane; });
+        let planes = [
+            "(0 0 64) (64 0 64) (64 0 0)",
+            "(0 64 0) (64 64 0) (64 64 64)",
+            "(0 0 0) (0 64 0) (0 64 64)",
+            "(64 0 64) (64 64 64) (64 64 0)",
+        ];
+        let mut solid: Block<&str> = Block::new("solid", vec![Property::new("id", "1")], vec![]);
+        solid.blocks = planes.iter().map(|plane| side(*plane)).collect();
+        solid.blocks.push(crate::block!(editor {}));
+        assert_eq!(solid.side_count(), 4);
+        assert!(solid.is_valid_solid());
+
+        let mut not_solid = solid.clone();
+        not_solid.name = "entity";
+        assert!(!not_solid.is_valid_solid());
+
+        let mut too_few = solid.clone();
+        too_few.blocks.remove(0);
+        assert_eq!(too_few.side_count(), 3);
+        assert!(!too_few.is_valid_solid());
+
+        let mut bad_plane = solid.clone();
+        bad_plane.blocks[1] = side("(0 0 0) (1 1 1)");
+        assert!(!bad_plane.is_valid_solid());
+
+        let mut no_plane = solid;
+        no_plane.blocks[2].props.pop();
+        assert!(!no_plane.is_valid_solid());
+    }
+
+    #[test]
+    fn sides_mut() {
+        let mut vmf = crate::parse::<String, ()>(
+            r#"world { solid { side { "material" "A" } side { "material" "B" } } }
+            entity { solid { side { "material" "C" } } }
+            hidden { solid { side { "material" "D" } } }"#,
+        )
+        .unwrap();
+        assert_eq!(vmf.sides().count(), 4);
+
+        for side in vmf.sides_mut() {
+            let prop = side.ensure_property("material", "");
+            prop.value = format!("DEV/{}", prop.value);
+        }
+        let materials: Vec<_> = vmf.sides().map(|side| side.props[0].value.as_str()).collect();
+        assert_eq!(materials, vec!["DEV/A", "DEV/B", "DEV/C", "DEV/D"]);
+
+        // nested sides are skipped by both
+        let mut vmf = crate::parse::<String, ()>("world { solid { side { side {} } } }").unwrap();
+        assert_eq!(vmf.sides().count(), 1);
+        assert_eq!(vmf.sides_mut().count(), 1);
+    }
+
+    #[test]
+    fn entities_near() {
+        let vmf = crate::parse::<&str, ()>(
+            r#"world { "id" "1" }
+            entity { "targetname" "origin" "origin" "0 0 0" }
+            entity { "targetname" "edge" "origin" "0 30 40" }
+            entity { "targetname" "far" "origin" "100 0 0" }
+            entity { "targetname" "brush" solid {} }
+            entity { "targetname" "broken" "origin" "0 0" }
+            hidden { entity { "targetname" "hidden" "origin" "-10 0 0" } }"#,
+        )
+        .unwrap();
+        let names = |point, radius| -> Vec<_> {
+            vmf.entities_near(point, radius).iter().map(|e| e.props[0].value).collect()
+        };
+        assert_eq!(names([0.0, 0.0, 0.0], 50.0), vec!["origin", "edge", "hidden"]);
+        assert_eq!(names([0.0, 0.0, 0.0], 49.9), vec!["origin", "hidden"]);
+        assert_eq!(names([90.0, 0.0, 0.0], 10.0), vec!["far"]);
+        assert!(names([0.0, 0.0, 1000.0], 1.0).is_empty());
+    }
+
+    #[test]
+    fn block_id() {
+        let block: Block<&str> = crate::block!(solid { "id" "7"; });
+        assert_eq!(block.id(), Some(7));
+        let block: Block<&str> = crate::block!(solid { "id" "not a number"; });
+        assert_eq!(block.id(), None);
+        let block: Block<&str> = crate::block!(solid { "classname" "1"; });
+        assert_eq!(block.id(), None);
+    }
+
+    #[test]
+    fn strip_editor_metadata() {
+        let input = r#"visgroups { visgroup { "visgroupid" "1" } }
+            world {
+                "id" "1"
+                solid { "id" "2" side { "id" "3" } editor { "color" "0 255 0" "visgroupid" "1" } }
+            }
+            entity { "id" "4" "classname" "light" editor { "visgroupid" "1" } }
+            cameras { "activecamera" "-1" }
+            cordon { "active" "0" }"#;
+
+        let mut vmf = crate::parse::<&str, ()>(input).unwrap();
+        vmf.remove_visgroups();
+        assert_eq!(vmf.blocks.len(), 4);
+        assert_eq!(
+            vmf.blocks[0].blocks[0].blocks[1].props,
+            vec![Property::new("color", "0 255 0")]
+        );
+        assert!(vmf.blocks[1].blocks[0].props.is_empty());
+
+        let mut vmf = crate::parse::<&str, ()>(input).unwrap();
+        vmf.strip_editor_metadata();
+        let names: Vec<_> = vmf.blocks.iter().map(|block| block.name).collect();
+        assert_eq!(names, ["world", "entity"]);
+        let solid = &vmf.blocks[0].blocks[0];
+        assert_eq!(solid.blocks.len(), 1);
+        assert_eq!(solid.blocks[0].name, "side");
+        assert!(vmf.blocks[1].blocks.is_empty());
+        assert_eq!(vmf.blocks[1].props.len(), 2);
+    }
+
+    #[test]
+    fn all_property_keys() {
+        let block: Block<&str> = crate::block!(world {
+            "id" "1";
+            "classname" "worldspawn";
+            solid {
+                "id" "2";
+                side { "id" "3"; "material" "TOOLS/TOOLSNODRAW"; }
+                editor { "color" "0 255 0"; }
+            }
+            hidden {}
+        });
+        let keys = block.all_property_keys();
+        assert_eq!(keys, HashSet::from(["id", "classname", "material", "color"]));
+        assert!(Block::<&str>::new("empty", vec![], vec![]).all_property_keys().is_empty());
+    }
+
+    #[test]
+    fn get() {
+        let mut block: Block<String> = crate::block!(entity {
+            "classname" "func_instance";
+            "group" "first";
+            "origin" "0 0 0";
+            "group" "second";
+        });
+        assert_eq!(block.get("origin").map(String::as_str), Some("0 0 0"));
+        assert_eq!(block.get("group").map(String::as_str), Some("first"), "first match wins");
+        assert_eq!(block.get("Origin"), None);
+        assert_eq!(block.get_all("group").collect::<Vec<_>>(), ["first", "second"]);
+        assert_eq!(block.get_all("missing").count(), 0);
+
+        *block.get_mut("group").unwrap() = "changed".to_string();
+        assert_eq!(block.get_all("group").collect::<Vec<_>>(), ["changed", "second"]);
+        assert!(block.get_mut("missing").is_none());
+
+        let borrowed: Block<&str> = crate::block!(entity { "origin" "1 2 3"; });
+        assert_eq!(borrowed.get("origin"), Some(&"1 2 3"));
+    }
+
+    #[test]
+    fn find_blocks() {
+        let mut vmf = crate::parse::<String, ()>(
+            r#"world { solid { "id" "1" } hidden { solid { "id" "2" } } solid { "id" "3" } }
+            entity { "id" "4" editor { "color" "0 0 0" } }"#,
+        )
+        .unwrap();
+
+        let world = vmf.find_block("world").unwrap();
+        assert_eq!(world.find_block("solid").unwrap().id(), Some(1));
+        let ids: Vec<_> = world.find_blocks("solid").map(|solid| solid.id()).collect();
+        assert_eq!(ids, [Some(1), Some(3)], "only direct children");
+        assert!(world.find_block("side").is_none());
+        assert_eq!(vmf.find_blocks("editor").count(), 0);
+
+        let entity = vmf.find_block_mut("entity").unwrap();
+        let editor = entity.find_block_mut("editor").unwrap();
+        *editor.get_mut("color").unwrap() = "255 0 0".to_string();
+        assert_eq!(vmf.blocks[1].blocks[0].get("color").unwrap(), "255 0 0");
+
+        let world = vmf.find_block_mut("world").unwrap();
+        for solid in world.find_blocks_mut("solid") {
+            solid.props.clear();
+        }
+        assert!(vmf.blocks[0].blocks[0].props.is_empty());
+        assert_eq!(vmf.blocks[0].blocks[1].blocks[0].id(), Some(2));
+        assert!(vmf.blocks[0].blocks[2].props.is_empty());
+    }
+
+    #[test]
+    fn map_names() {
+        let mut vmf = crate::parse::<String, ()>(
+            r#"world { "classname" "worldspawn" solid { side {} side {} } } entity {}"#,
+        )
+        .unwrap();
+        let mut seen = Vec::new();
+        vmf.map_names(|name| {
+            seen.push(name.to_string());
+            name.to_uppercase()
+        });
+        assert_eq!(seen, ["root", "world", "solid", "side", "side", "entity"]);
+
+        let names: Vec<_> = vmf.iter_tree().map(|block| block.name.as_str()).collect();
+        assert_eq!(names, ["ROOT", "WORLD", "SOLID", "SIDE", "SIDE", "ENTITY"]);
+        assert_eq!(vmf.blocks[0].props[0], Property::new("classname", "worldspawn"));
+    }
+}
@@ -0,0 +1,134 @@
+//! Blocks that keep the original order of their properties and sub blocks.
+
+use super::display::{PadAdapter, TrimAdapter};
+use super::*;
+use std::fmt::{self, Display, Write};
+
+/// A list of [`OrderedBlock`]s, representing an entire Vmf file, see [`parse_ordered()`](crate::parse_ordered).
+/// Like [`Vmf`] but keeps properties that come after sub blocks in place.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct OrderedVmf<S> {
+    pub blocks: Vec<OrderedBlock<S>>,
+}
+
+/// A named block containing properties and other blocks in the order they were parsed.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct OrderedBlock<S> {
+    pub name: S,
+    pub items: Vec<Item<S>>,
+}
+
+/// A property or a block inside of an [`OrderedBlock`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Item<S> {
+    Prop(Property<S, S>),
+    Block(OrderedBlock<S>),
+}
+
+impl<S> OrderedBlock<S> {
+    pub fn new<T: Into<S>>(name: T, items: Vec<Item<S>>) -> Self {
+        Self { name: name.into(), items }
+    }
+}
+
+// Conversions, losing or making up the order
+
+impl<'a, S: From<&'a str>> From<OrderedVmf<S>> for Vmf<S> {
+    fn from(vmf: OrderedVmf<S>) -> Self {
+        Vmf::new(vmf.blocks.into_iter().map(Block::from).collect())
+    }
+}
+
+impl<S> From<OrderedBlock<S>> for Block<S> {
+    /// Moves the properties and blocks into separate lists, losing their relative order.
+    fn from(ordered: OrderedBlock<S>) -> Self {
+        let mut block = Block { name: ordered.name, props: Vec::new(), blocks: Vec::new() };
+        for item in ordered.items {
+            match item {
+                Item::Prop(prop) => block.props.push(prop),
+                Item::Block(child) => block.blocks.push(child.into()),
+            }
+        }
+        block
+    }
+}
+
+impl<S> From<Block<S>> for OrderedBlock<S> {
+    /// Puts the properties before the blocks.
+    fn from(block: Block<S>) -> Self {
+        let props = block.props.into_iter().map(Item::Prop);
+        let blocks = block.blocks.into_iter().map(|child| Item::Block(child.into()));
+        Self { name: block.name, items: props.chain(blocks).collect() }
+    }
+}
+
+impl<S: Display> Display for OrderedVmf<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut iter = self.blocks.iter().peekable();
+        while let Some(block) = iter.next() {
+            write!(f, "{block}")?;
+            if iter.peek().is_some() {
+                // print newline if not last iteration
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<S: Display> Display for OrderedBlock<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(TrimAdapter::new(f), "{}", self.name)?;
+        writeln!(f)?;
+
+        let mut adapter = PadAdapter::new(f);
+        writeln!(adapter, "{{")?;
+        for item in self.items.iter() {
+            match item {
+                Item::Prop(prop) => writeln!(adapter, "{prop}")?,
+                Item::Block(block) => writeln!(adapter, "{block}")?,
+            }
+        }
+
+        write!(f, "}}")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ordered_round_trip() {
+        let input = "entity
+{
+\t\"classname\" \"func_detail\"
+\tsolid
+\t{
+\t\t\"id\" \"2\"
+\t}
+\t\"id\" \"1\"
+\teditor
+\t{
+\t}
+\t\"origin\" \"0 0 0\"
+}
+world
+{
+}";
+        let ordered = crate::parse_ordered::<&str, ()>(input).unwrap();
+        assert_eq!(ordered.to_string(), input);
+        assert_eq!(
+            ordered.blocks[0].items[2],
+            Item::Prop(Property::new("id", "1")),
+            "property after a block stays in place"
+        );
+
+        // plain parse moves properties first
+        let plain = crate::parse::<&str, ()>(input).unwrap();
+        assert_ne!(plain.to_string(), input);
+        assert_eq!(Vmf::from(ordered.clone()), plain);
+        assert_eq!(Block::from(ordered.blocks[0].clone()), plain.blocks[0]);
+    }
+}
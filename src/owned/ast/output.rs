@@ -0,0 +1,136 @@
+//! Entity outputs stored in `connections` blocks.
+
+use super::Block;
+
+/// Delimiter used by newer versions of Hammer.
+const ESC_DELIMITER: char = '\x1b';
+/// Delimiter used by older versions of Hammer.
+const LEGACY_DELIMITER: char = ',';
+
+/// A single entity output, the value of a property in a `connections` block.
+/// The key of the property is the output name, such as `OnTrigger`.
+///
+/// The value is in the form `target,input,param,delay,times`, newer versions use
+/// the ESC character `\x1b` instead of a comma.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct EntityOutput {
+    /// The targetname or classname of the entity that receives the input.
+    pub target: String,
+    /// The input fired on the target.
+    pub input: String,
+    /// Parameter passed with the input, often empty.
+    pub param: String,
+    /// Delay in seconds before the input is fired.
+    pub delay: f32,
+    /// Times the output can fire, `-1` for infinite.
+    pub times: i32,
+}
+
+/// Parses the value of an entity output, see [`EntityOutput`].
+/// Detects whether the value is ESC or comma delimited.
+/// Returns `None` if there are not exactly 5 fields or if the delay or times are not numbers.
+pub fn parse_output(value: &str) -> Option<EntityOutput> {
+    let delimiter = if value.contains(ESC_DELIMITER) { ESC_DELIMITER } else { LEGACY_DELIMITER };
+    let mut fields = value.split(delimiter);
+
+    let target = fields.next()?;
+    let input = fields.next()?;
+    let param = fields.next()?;
+    let delay = fields.next()?.trim().parse().ok()?;
+    let times = fields.next()?.trim().parse().ok()?;
+    if fields.next().is_some() {
+        return None;
+    }
+
+    Some(EntityOutput {
+        target: target.to_string(),
+        input: input.to_string(),
+        param: param.to_string(),
+        delay,
+        times,
+    })
+}
+
+impl<S: AsRef<str>> Block<S> {
+    /// Parses every output in the `connections` block of this entity, along with its
+    /// output name. Each output may be ESC or comma delimited, see [`parse_output`].
+    /// Outputs that fail to parse are skipped. Empty if there is no `connections` block.
+    pub fn entity_connections(&self) -> Vec<(String, EntityOutput)> {
+        let Some(connections) =
+            self.blocks.iter().find(|block| block.name.as_ref() == "connections")
+        else {
+            return Vec::new();
+        };
+        connections
+            .props
+            .iter()
+            .filter_map(|prop| {
+                let output = parse_output(prop.value.as_ref())?;
+                Some((prop.key.as_ref().to_string(), output))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_delimiter() {
+        let truth = EntityOutput {
+            target: "door_1".to_string(),
+            input: "Open".to_string(),
+            param: "".to_string(),
+            delay: 0.5,
+            times: -1,
+        };
+        assert_eq!(parse_output("door_1,Open,,0.5,-1"), Some(truth));
+        assert_eq!(parse_output("door_1,Open,,0.5"), None);
+        assert_eq!(parse_output("door_1,Open,,0.5,-1,extra"), None);
+        assert_eq!(parse_output("door_1,Open,,soon,-1"), None);
+    }
+
+    #[test]
+    fn esc_delimiter() {
+        let truth = EntityOutput {
+            target: "relay".to_string(),
+            input: "AddOutput".to_string(),
+            param: "targetname a,b".to_string(),
+            delay: 0.0,
+            times: 1,
+        };
+        assert_eq!(parse_output("relay\x1bAddOutput\x1btargetname a,b\x1b0\x1b1"), Some(truth));
+        assert_eq!(parse_output("relay\x1bAddOutput\x1b\x1b0"), None);
+    }
+
+    #[test]
+    fn entity_connections() {
+        let vmf = crate::parse::<&str, ()>(
+            "entity {
+                \"classname\" \"trigger_once\"
+                connections {
+                    \"OnTrigger\" \"door_1,Open,,0.5,-1\"
+                    \"OnTrigger\" \"relay\x1bTrigger\x1b\x1b0\x1b1\"
+                    \"OnStartTouch\" \"broken\"
+                }
+            }
+            entity { \"classname\" \"light\" }",
+        )
+        .unwrap();
+
+        let connections = vmf.blocks[0].entity_connections();
+        let summary: Vec<_> = connections
+            .iter()
+            .map(|(name, output)| (name.as_str(), output.target.as_str(), output.input.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("OnTrigger", "door_1", "Open"), ("OnTrigger", "relay", "Trigger")]
+        );
+        assert_eq!(connections[0].1.delay, 0.5);
+        assert_eq!(connections[1].1.times, 1);
+
+        assert!(vmf.blocks[1].entity_connections().is_empty());
+    }
+}
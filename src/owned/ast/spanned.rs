@@ -0,0 +1,148 @@
+//! Strings that remember where they are in the input.
+
+use super::*;
+use std::fmt::{self, Display};
+use std::ops::Range;
+
+/// A string and the byte range it was parsed from, see [`parse_spanned()`](crate::parse_spanned).
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Spanned<S> {
+    pub value: S,
+    pub span: Range<usize>,
+}
+
+impl<'a> Spanned<&'a str> {
+    /// Create from a slice of `input`. Strings that are not part of `input`,
+    /// such as the name of the root block, get an empty span at `0`.
+    pub(crate) fn from_slice(input: &'a str, value: &'a str) -> Self {
+        let input_start = input.as_ptr() as usize;
+        let start = value.as_ptr() as usize;
+        let span = if input_start <= start && start + value.len() <= input_start + input.len() {
+            start - input_start..start - input_start + value.len()
+        } else {
+            0..0
+        };
+        Self { value, span }
+    }
+}
+
+impl<S> Vmf<Spanned<S>> {
+    /// Discards the spans, returning the plain ast.
+    pub fn strip_spans(self) -> Vmf<S> {
+        Vmf { inner: self.inner.strip_spans() }
+    }
+}
+
+impl<S> Block<Spanned<S>> {
+    /// Discards the spans, returning the plain ast.
+    pub fn strip_spans(self) -> Block<S> {
+        Block {
+            name: self.name.value,
+            props: self
+                .props
+                .into_iter()
+                .map(|prop| Property { key: prop.key.value, value: prop.value.value })
+                .collect(),
+            blocks: self.blocks.into_iter().map(Block::strip_spans).collect(),
+        }
+    }
+}
+
+impl<'a> Block<&'a str> {
+    /// Attach spans relative to `input`, which this block was parsed from.
+    pub(crate) fn into_spanned(self, input: &'a str) -> Block<Spanned<&'a str>> {
+        Block {
+            name: Spanned::from_slice(input, self.name),
+            props: self
+                .props
+                .into_iter()
+                .map(|prop| Property {
+                    key: Spanned::from_slice(input, prop.key),
+                    value: Spanned::from_slice(input, prop.value),
+                })
+                .collect(),
+            blocks: self.blocks.into_iter().map(|block| block.into_spanned(input)).collect(),
+        }
+    }
+}
+
+/// Whether the opening brace of a block is on the same line as its name,
+/// see [`parse_with_brace_styles()`](crate::parse_with_brace_styles).
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum BraceStyle {
+    /// `name {`
+    SameLine,
+    /// `name` then `{` on a later line, what Hammer writes.
+    NextLine,
+}
+
+impl<'a> Block<&'a str> {
+    /// Returns the [`BraceStyle`] of every child block, depth first with parents before children,
+    /// using where the names are in `input`, which this block was parsed from.
+    pub(crate) fn brace_styles(&self, input: &'a str) -> Vec<BraceStyle> {
+        self.iter_tree()
+            .skip(1)
+            .map(|block| {
+                let name_end = Spanned::from_slice(input, block.name).span.end;
+                let before_brace = input[name_end..].split('{').next().unwrap_or_default();
+                if before_brace.contains('\n') {
+                    BraceStyle::NextLine
+                } else {
+                    BraceStyle::SameLine
+                }
+            })
+            .collect()
+    }
+}
+
+impl<S: AsRef<str>> AsRef<str> for Spanned<S> {
+    fn as_ref(&self) -> &str {
+        self.value.as_ref()
+    }
+}
+
+impl<S: Display> Display for Spanned<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strip_spans() {
+        let input = "world\n{\n\t\"id\" \"1\"\n\tsolid\n\t{\n\t}\n}";
+        let spanned = crate::parse_spanned::<()>(input).unwrap();
+
+        let world = &spanned.blocks[0];
+        assert_eq!(world.name, Spanned { value: "world", span: 0..5 });
+        assert_eq!(world.props[0].key, Spanned { value: "id", span: 10..12 });
+        assert_eq!(&input[world.props[0].value.span.clone()], "1");
+        assert_eq!(&input[world.blocks[0].name.span.clone()], "solid");
+        assert_eq!(spanned.name.span, 0..0);
+
+        let plain = crate::parse::<&str, ()>(input).unwrap();
+        assert_eq!(spanned.to_string(), plain.to_string());
+        assert_eq!(spanned.clone().strip_spans(), plain);
+    }
+
+    #[test]
+    fn brace_styles() {
+        let input = "a {\n}\nb\n{\n\tc {}\n\td\r\n\t{\n\t}\n}\ne{}";
+        let (vmf, styles) = crate::parse_with_brace_styles::<()>(input).unwrap();
+        let names: Vec<_> = vmf.iter_tree().skip(1).map(|block| block.name).collect();
+        assert_eq!(names, vec!["a", "b", "c", "d", "e"]);
+        assert_eq!(
+            styles,
+            vec![
+                BraceStyle::SameLine,
+                BraceStyle::NextLine,
+                BraceStyle::SameLine,
+                BraceStyle::NextLine,
+                BraceStyle::SameLine
+            ]
+        );
+    }
+}
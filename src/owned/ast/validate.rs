@@ -0,0 +1,130 @@
+//! Checks that a tree will display as valid vmf.
+
+use super::*;
+use std::fmt::{self, Display};
+
+/// Something that would make the output of [`Display`] fail to parse,
+/// see [`Block::validate`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ValidationWarning {
+    /// A block with an empty name, or only whitespace.
+    EmptyName,
+    /// A block name, not counting leading and trailing whitespace, with characters other than ASCII alphanumerics and underscores.
+    InvalidName(String),
+    /// A property key containing a double quote.
+    QuoteInKey(String),
+    /// A property value containing a double quote.
+    QuoteInValue { key: String, value: String },
+}
+
+impl<S: AsRef<str>> Vmf<S> {
+    /// Returns `Err` with every [`ValidationWarning`] if there are any, see [`Block::validate`].
+    /// Handy for `vmf.assert_valid()?` after building or editing a tree.
+    pub fn assert_valid(&self) -> Result<(), Vec<ValidationWarning>> {
+        let warnings = self.validate();
+        if warnings.is_empty() {
+            Ok(())
+        } else {
+            Err(warnings)
+        }
+    }
+}
+
+impl<S: AsRef<str>> Block<S> {
+    /// Checks this block and all of its children for anything that would
+    /// display as invalid vmf.
+    pub fn validate(&self) -> Vec<ValidationWarning> {
+        let mut warnings = Vec::new();
+        self.validate_into(&mut warnings);
+        warnings
+    }
+
+    fn validate_into(&self, warnings: &mut Vec<ValidationWarning>) {
+        // leading and trailing whitespace is trimmed by `Display`
+        let name = self.name.as_ref().trim();
+        if name.is_empty() {
+            warnings.push(ValidationWarning::EmptyName);
+        } else if !is_valid_name(name) {
+            warnings.push(ValidationWarning::InvalidName(name.to_string()));
+        }
+
+        for prop in self.props.iter() {
+            let (key, value) = (prop.key.as_ref(), prop.value.as_ref());
+            if key.contains('"') {
+                warnings.push(ValidationWarning::QuoteInKey(key.to_string()));
+            }
+            if value.contains('"') {
+                warnings.push(ValidationWarning::QuoteInValue {
+                    key: key.to_string(),
+                    value: value.to_string(),
+                });
+            }
+        }
+
+        for block in self.blocks.iter() {
+            block.validate_into(warnings);
+        }
+    }
+}
+
+/// Checks if a non-empty block name will parse, ASCII alphanumerics and underscores only.
+pub(super) fn is_valid_name(name: &str) -> bool {
+    name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+impl Display for ValidationWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyName => write!(f, "empty block name"),
+            Self::InvalidName(name) => write!(f, "invalid block name {name:?}"),
+            Self::QuoteInKey(key) => write!(f, "quote in property key {key:?}"),
+            Self::QuoteInValue { key, value } => {
+                write!(f, "quote in value {value:?} of property {key:?}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ValidationWarning {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assert_valid() {
+        let mut vmf = crate::parse::<String, ()>(
+            r#"world { "id" "1" solid { side { "material" "TOOLS/TOOLSNODRAW" } } }"#,
+        )
+        .unwrap();
+        assert_eq!(vmf.assert_valid(), Ok(()));
+
+        vmf.blocks[0].name = "".to_string();
+        vmf.blocks[0].blocks[0].name = "so lid".to_string();
+        vmf.blocks[0].props.push(Property::new("key\"", "value"));
+        vmf.blocks[0].blocks[0].blocks[0].props[0].value = "\"".to_string();
+        assert_eq!(
+            vmf.assert_valid(),
+            Err(vec![
+                ValidationWarning::EmptyName,
+                ValidationWarning::QuoteInKey("key\"".to_string()),
+                ValidationWarning::InvalidName("so lid".to_string()),
+                ValidationWarning::QuoteInValue {
+                    key: "material".to_string(),
+                    value: "\"".to_string()
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn trimmed_names() {
+        let mut block: Block<String> = crate::block!(world { solid {} });
+        block.blocks[0].name = " solid\t".to_string();
+        assert_eq!(block.validate(), vec![]);
+        block.blocks[0].name = " \n".to_string();
+        assert_eq!(block.validate(), vec![ValidationWarning::EmptyName]);
+        block.blocks[0].name = " so lid ".to_string();
+        assert_eq!(block.validate(), vec![ValidationWarning::InvalidName("so lid".to_string())]);
+    }
+}
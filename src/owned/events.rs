@@ -0,0 +1,164 @@
+//! Pull parser that emits events instead of building a [`Vmf`](crate::ast::Vmf).
+
+use crate::owned::parsers::nom_prelude::*;
+use crate::{
+    nom_helpers::ParseErrorExt,
+    owned::parsers::{close_brace, identifier, is_not_no_fail, open_brace, property},
+};
+use std::marker::PhantomData;
+
+/// A single event emitted by [`events()`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum VmfEvent<'a> {
+    /// The name of a block and its opening brace.
+    BlockStart(&'a str),
+    /// The closing brace of the last started block.
+    BlockEnd,
+    /// A key-value pair inside a block.
+    Property(&'a str, &'a str),
+    /// The text of a comment, without the leading `//`.
+    Comment(&'a str),
+}
+
+/// Iterator over [`VmfEvent`]s, see [`events()`].
+/// Stops after the first error.
+#[derive(Debug, Clone)]
+pub struct Events<'a, E> {
+    input: &'a str,
+    depth: usize,
+    done: bool,
+    _error: PhantomData<E>,
+}
+
+/// Parse a `&str` into a stream of [`VmfEvent`]s without building the full AST.
+/// Whitespace is discarded, comments are kept.
+///
+/// Accepts the same input as [`parse()`](crate::parse) and the same error types.
+///
+/// # Examples
+///
+/// ```rust
+/// use vmf_parser_nom::events::{events, VmfEvent};
+///
+/// let input = "block{\"key\" \"value\"}";
+/// let events: Vec<_> = events::<()>(input).collect::<Result<_, _>>().unwrap();
+/// assert_eq!(
+///     events,
+///     vec![VmfEvent::BlockStart("block"), VmfEvent::Property("key", "value"), VmfEvent::BlockEnd]
+/// );
+/// ```
+pub fn events<'a, E>(input: &'a str) -> Events<'a, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    Events { input, depth: 0, done: false, _error: PhantomData }
+}
+
+impl<'a, E> Events<'a, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    /// Parse the next event, input must not start with whitespace.
+    fn next_event(&mut self) -> Result<(&'a str, VmfEvent<'a>), E> {
+        let input = self.input;
+
+        if let Ok((i, text)) = preceded(tag::<_, _, E>("//"), is_not_no_fail("\n\r"))(input) {
+            return Ok((i, VmfEvent::Comment(text)));
+        }
+        if self.depth > 0 {
+            if let Ok((i, ())) = close_brace::<E>(input) {
+                self.depth -= 1;
+                return Ok((i, VmfEvent::BlockEnd));
+            }
+            if let Ok((i, prop)) = property::<&str, E>(input) {
+                return Ok((i, VmfEvent::Property(prop.key, prop.value)));
+            }
+        }
+        if let Ok((i, name)) = terminated(identifier::<E>, open_brace)(input) {
+            self.depth += 1;
+            return Ok((i, VmfEvent::BlockStart(name)));
+        }
+
+        if self.depth > 0 {
+            Err(E::from_context(input, "no parsers matched in block"))
+        } else {
+            Err(E::from_context(input, "expected block"))
+        }
+    }
+}
+
+impl<'a, E> Iterator for Events<'a, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    type Item = Result<VmfEvent<'a>, E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        if let Ok((input, _)) = multispace0::<_, E>(self.input) {
+            self.input = input;
+        }
+        if self.input.is_empty() {
+            self.done = true;
+            if self.depth > 0 {
+                return Some(Err(E::from_context(self.input, "expected '}' found EOF")));
+            }
+            return None;
+        }
+
+        match self.next_event() {
+            Ok((input, event)) => {
+                self.input = input;
+                Some(Ok(event))
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn event_sequence() {
+        let input = "// comment
+world
+{
+\t\"id\" \"1\"
+\tsolid
+\t{
+\t\t// nested comment
+\t}
+}
+entity{}";
+        let truth = vec![
+            VmfEvent::Comment(" comment"),
+            VmfEvent::BlockStart("world"),
+            VmfEvent::Property("id", "1"),
+            VmfEvent::BlockStart("solid"),
+            VmfEvent::Comment(" nested comment"),
+            VmfEvent::BlockEnd,
+            VmfEvent::BlockEnd,
+            VmfEvent::BlockStart("entity"),
+            VmfEvent::BlockEnd,
+        ];
+        let output: Vec<_> = events::<VerboseError<_>>(input).collect::<Result<_, _>>().unwrap();
+        assert_eq!(truth, output);
+    }
+
+    #[test]
+    fn event_errors() {
+        let output: Vec<_> = events::<()>("block{").collect();
+        assert_eq!(output, vec![Ok(VmfEvent::BlockStart("block")), Err(())]);
+
+        let output: Vec<_> = events::<()>("\"key\" \"value\"").collect();
+        assert_eq!(output, vec![Err(())]);
+    }
+}
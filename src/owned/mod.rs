@@ -1,4 +1,4 @@
 //! Contains vmf with vecs
-//! TODO: arena feature
 pub mod ast;
+pub mod events;
 pub mod parsers;
\ No newline at end of file
@@ -3,9 +3,9 @@
 pub(crate) mod nom_prelude {
     pub use nom::{
         branch::alt,
-        bytes::complete::{is_not, tag, take_until, take_while},
+        bytes::complete::{is_not, tag, take_till, take_until, take_while, take_while1},
         character::complete::{
-            alphanumeric0, alphanumeric1, char, multispace0, multispace1, one_of,
+            alphanumeric0, alphanumeric1, anychar, char, multispace0, multispace1, one_of,
         },
         combinator::{fail, map, map_opt, map_res, opt, recognize, success, value},
         error::{context, ContextError, ErrorKind, ParseError, VerboseError, VerboseErrorKind},
@@ -17,9 +17,204 @@ pub(crate) mod nom_prelude {
 
 use crate::{
     nom_helpers::ParseErrorExt,
-    owned::ast::{Block, Property, Vmf},
+    owned::ast::{Block, Item, OrderedBlock, Property, Vmf},
 };
 use nom_prelude::*;
+use std::borrow::Cow;
+
+/// Options for parsing, see [`parse_with_config()`](crate::parse_with_config).
+/// The default is the same as [`parse()`](crate::parse).
+///
+/// New options may be added, so build it from the default with the `with_*` setters:
+///
+/// ```rust
+/// use vmf_parser_nom::parsers::ParseConfig;
+///
+/// let config = ParseConfig::default().with_strip_bom(true).with_require_complete(true);
+/// assert!(config.strip_bom && config.require_complete);
+/// ```
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct ParseConfig {
+    /// Character properties are quoted by, `"` by default.
+    /// See [`block_with_quote`].
+    pub quote_char: char,
+    /// Characters allowed in block names, ASCII alphanumerics and underscores by default.
+    /// See [`block_with_ident_pred`].
+    pub ident_pred: fn(char) -> bool,
+    /// Skip a leading UTF-8 byte order mark.
+    pub strip_bom: bool,
+    /// Error if there is anything other than whitespace and comments after the last block,
+    /// instead of ignoring the rest of the input.
+    pub require_complete: bool,
+    /// Join lines ending in a `\`, see
+    /// [`join_line_continuations`](crate::preprocess::join_line_continuations).
+    /// Rewrites the input, so only applied by [`parse_with_config_owned()`](crate::parse_with_config_owned)
+    /// or [`ParseConfig::preprocess`].
+    pub join_line_continuations: bool,
+    /// Convert `\r\n` and `\r` line endings to `\n`, see
+    /// [`normalize_newlines`](crate::preprocess::normalize_newlines).
+    /// Rewrites the input, so only applied by [`parse_with_config_owned()`](crate::parse_with_config_owned)
+    /// or [`ParseConfig::preprocess`].
+    pub normalize_newlines: bool,
+    /// ASCII lowercase block names, so `SOLID` can be matched as `solid`, see
+    /// [`lowercase_block_names`](crate::preprocess::lowercase_block_names).
+    /// Rewrites the input, so only applied by [`parse_with_config_owned()`](crate::parse_with_config_owned)
+    /// or [`ParseConfig::preprocess`].
+    pub lowercase_names: bool,
+    /// Lenient mode, allow properties outside of any block and collect them into the
+    /// root's [`props`](Block::props). Strict VMF requires everything to be inside a block.
+    pub root_properties: bool,
+    /// Unescape `\t`, `\n`, `\r`, and `\\` in keys and values, the opposite of
+    /// [`DisplayOptions::escape_control`](crate::ast::DisplayOptions::escape_control), see
+    /// [`unescape_control`](crate::preprocess::unescape_control).
+    /// Rewrites keys and values, so only applied by
+    /// [`parse_with_config_owned()`](crate::parse_with_config_owned) or [`ParseConfig::postprocess`].
+    pub unescape_control: bool,
+    /// A backslash escapes the next character in keys and values, so `\"` does not end them.
+    /// They are kept as is, without unescaping. See [`string_escaped`].
+    pub escaped_quotes: bool,
+}
+
+impl Default for ParseConfig {
+    fn default() -> Self {
+        Self {
+            quote_char: '"',
+            ident_pred: is_identifier_char,
+            strip_bom: false,
+            require_complete: false,
+            join_line_continuations: false,
+            normalize_newlines: false,
+            lowercase_names: false,
+            root_properties: false,
+            unescape_control: false,
+            escaped_quotes: false,
+        }
+    }
+}
+
+impl ParseConfig {
+    /// Sets [`quote_char`](Self::quote_char).
+    pub fn with_quote_char(mut self, quote_char: char) -> Self {
+        self.quote_char = quote_char;
+        self
+    }
+
+    /// Sets [`ident_pred`](Self::ident_pred).
+    pub fn with_ident_pred(mut self, ident_pred: fn(char) -> bool) -> Self {
+        self.ident_pred = ident_pred;
+        self
+    }
+
+    /// Sets [`strip_bom`](Self::strip_bom).
+    pub fn with_strip_bom(mut self, strip_bom: bool) -> Self {
+        self.strip_bom = strip_bom;
+        self
+    }
+
+    /// Sets [`require_complete`](Self::require_complete).
+    pub fn with_require_complete(mut self, require_complete: bool) -> Self {
+        self.require_complete = require_complete;
+        self
+    }
+
+    /// Sets [`join_line_continuations`](Self::join_line_continuations).
+    pub fn with_join_line_continuations(mut self, join_line_continuations: bool) -> Self {
+        self.join_line_continuations = join_line_continuations;
+        self
+    }
+
+    /// Sets [`normalize_newlines`](Self::normalize_newlines).
+    pub fn with_normalize_newlines(mut self, normalize_newlines: bool) -> Self {
+        self.normalize_newlines = normalize_newlines;
+        self
+    }
+
+    /// Sets [`lowercase_names`](Self::lowercase_names).
+    pub fn with_lowercase_names(mut self, lowercase_names: bool) -> Self {
+        self.lowercase_names = lowercase_names;
+        self
+    }
+
+    /// Sets [`root_properties`](Self::root_properties).
+    pub fn with_root_properties(mut self, root_properties: bool) -> Self {
+        self.root_properties = root_properties;
+        self
+    }
+
+    /// Sets [`unescape_control`](Self::unescape_control).
+    pub fn with_unescape_control(mut self, unescape_control: bool) -> Self {
+        self.unescape_control = unescape_control;
+        self
+    }
+
+    /// Sets [`escaped_quotes`](Self::escaped_quotes).
+    pub fn with_escaped_quotes(mut self, escaped_quotes: bool) -> Self {
+        self.escaped_quotes = escaped_quotes;
+        self
+    }
+
+    /// Applies the options that rewrite the input, returning it unchanged if there are none.
+    /// Call this before [`parse_with_config()`](crate::parse_with_config), as the output
+    /// cannot borrow from a rewritten input.
+    pub fn preprocess<'a>(&self, input: &'a str) -> Cow<'a, str> {
+        let mut input = Cow::Borrowed(input);
+        // before joining lines, so `\` then `\r` is a continuation too
+        if self.normalize_newlines {
+            if let Cow::Owned(normalized) = crate::preprocess::normalize_newlines(&input) {
+                input = Cow::Owned(normalized);
+            }
+        }
+        if self.join_line_continuations {
+            if let Cow::Owned(joined) =
+                crate::preprocess::join_line_continuations_with_quote(&input, self.quote_char)
+            {
+                input = Cow::Owned(joined);
+            }
+        }
+        if self.lowercase_names {
+            let quote = self.quote_char;
+            if let Cow::Owned(lowered) =
+                crate::preprocess::lowercase_block_names_with_quote(&input, quote)
+            {
+                input = Cow::Owned(lowered);
+            }
+        }
+        input
+    }
+
+    /// Applies the options that rewrite keys and values of a parsed [`Vmf`], borrowing
+    /// any that are unchanged. Call this after [`parse_with_config()`](crate::parse_with_config),
+    /// as borrowed output cannot point at rewritten text.
+    pub fn postprocess<'a>(&self, vmf: Vmf<&'a str>) -> Vmf<Cow<'a, str>> {
+        fn map<'a>(block: Block<&'a str>, unescape: bool) -> Block<Cow<'a, str>> {
+            let rewrite = |s: &'a str| match unescape {
+                true => crate::preprocess::unescape_control(s),
+                false => Cow::Borrowed(s),
+            };
+            Block {
+                name: Cow::Borrowed(block.name),
+                props: block
+                    .props
+                    .into_iter()
+                    .map(|prop| Property { key: rewrite(prop.key), value: rewrite(prop.value) })
+                    .collect(),
+                blocks: block.blocks.into_iter().map(|child| map(child, unescape)).collect(),
+            }
+        }
+        Vmf { inner: map(vmf.inner, self.unescape_control) }
+    }
+
+    /// Parser for a single [`Block`] using these options.
+    pub fn block<'a, O, E>(&self) -> impl FnMut(&'a str) -> IResult<&'a str, Block<O>, E>
+    where
+        O: From<&'a str>,
+        E: ParseError<&'a str> + ContextError<&'a str>,
+    {
+        let (quote, ident, escaped) = (self.quote_char, self.ident_pred, self.escaped_quotes);
+        move |input| block_impl(input, quote, ident, escaped)
+    }
+}
 
 /// Parses a [`Vmf`]. Discards any whitespace.
 pub fn vmf<'a, O, E>(input: &'a str) -> IResult<&'a str, Vmf<O>, E>
@@ -30,43 +225,191 @@ where
     map(many1(block), Vmf::new)(input)
 }
 
+/// Parses a [`Vmf`] like [`vmf`], with properties quoted by `quote` instead of `"`.
+pub fn vmf_with_quote<'a, O, E>(quote: char) -> impl FnMut(&'a str) -> IResult<&'a str, Vmf<O>, E>
+where
+    O: From<&'a str>,
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    map(many1(block_with_quote(quote)), Vmf::new)
+}
+
 /// Parses a [`Block`]. Discards any whitespace.
 pub fn block<'a, O, E>(input: &'a str) -> IResult<&'a str, Block<O>, E>
 where
     O: From<&'a str>,
     E: ParseError<&'a str> + ContextError<&'a str>,
 {
-    let (input, _) = many0_count(ignorable)(input)?;
-    let (input, name) = terminated(ignore_whitespace(identifier), open_brace)(input)?;
+    block_impl(input, '"', is_identifier_char, false)
+}
+
+/// Parses a [`Block`] like [`block`], with properties quoted by `quote` instead of `"`.
+pub fn block_with_quote<'a, O, E>(
+    quote: char,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Block<O>, E>
+where
+    O: From<&'a str>,
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    move |input| block_impl(input, quote, is_identifier_char, false)
+}
+
+/// Parses a [`Block`] like [`block`], with block names made of characters matching `pred`
+/// instead of ASCII alphanumerics and underscores. For formats related to vmf with
+/// different naming rules.
+pub fn block_with_ident_pred<'a, O, E>(
+    pred: fn(char) -> bool,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Block<O>, E>
+where
+    O: From<&'a str>,
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    move |input| block_impl(input, '"', pred, false)
+}
+
+fn block_impl<'a, O, E>(
+    input: &'a str,
+    quote: char,
+    ident: fn(char) -> bool,
+    escaped: bool,
+) -> IResult<&'a str, Block<O>, E>
+where
+    O: From<&'a str>,
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    let (mut input, name) = block_start(input, ident)?;
 
     let mut props = Vec::new();
     let mut blocks = Vec::new();
+    loop {
+        let (i, item) = body_item(input, property_impl(quote, escaped), |i| {
+            block_impl(i, quote, ident, escaped)
+        })?;
+        input = i;
+        match item {
+            BodyItem::Prop(prop) => props.push(prop),
+            BodyItem::Block(block) => blocks.push(block),
+            BodyItem::Ignored => {}
+            BodyItem::Close => break,
+        }
+    }
 
-    // let (input, _) = many0(alt((comment, add_prop, add_block)))(input)?;
+    Ok((input, Block { name: name.into(), props, blocks }))
+}
+
+/// Parses an [`OrderedBlock`], keeping properties and blocks in the order they appear.
+/// Discards any whitespace.
+pub fn ordered_block<'a, O, E>(input: &'a str) -> IResult<&'a str, OrderedBlock<O>, E>
+where
+    O: From<&'a str>,
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    let (mut input, name) = block_start(input, is_identifier_char)?;
+
+    let mut items = Vec::new();
+    loop {
+        let (i, item) = body_item(input, property, ordered_block)?;
+        input = i;
+        match item {
+            BodyItem::Prop(prop) => items.push(Item::Prop(prop)),
+            BodyItem::Block(block) => items.push(Item::Block(block)),
+            BodyItem::Ignored => {}
+            BodyItem::Close => break,
+        }
+    }
+
+    Ok((input, OrderedBlock { name: name.into(), items }))
+}
+
+/// Parses anything ignorable, the block name made of characters matching `ident`, and the open brace.
+fn block_start<'a, E>(input: &'a str, ident: fn(char) -> bool) -> IResult<&'a str, &'a str, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    let (input, _) = many0_count(ignorable)(input)?;
+    let name = context("bad identifier", take_while1(ident));
+    terminated(ignore_whitespace(name), open_brace)(input)
+}
+
+/// Parses one or more blocks and any properties between them at the top level of a file,
+/// see [`ParseConfig::root_properties`]. Stops at the first thing that is neither.
+pub(crate) fn root_items<'a, O, E>(
+    input: &'a str,
+    config: &ParseConfig,
+) -> IResult<&'a str, Vmf<O>, E>
+where
+    O: From<&'a str>,
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    let mut block = config.block::<O, E>();
+    let mut property = property_impl::<O, E>(config.quote_char, config.escaped_quotes);
 
-    // manual `alt` implementation to allow break or pushing or smth
+    let mut props = Vec::new();
+    let mut blocks = Vec::new();
     let mut input = input;
     loop {
-        // ugly loop
-        if let Ok((i, prop)) = property::<_, E>(input) {
+        let (i, _) = many0_count(ignorable::<E>)(input)?;
+        if let Ok((i, prop)) = property(i) {
             props.push(prop);
             input = i;
-        } else if let Ok((i, block)) = block::<_, E>(input) {
-            blocks.push(block);
-            input = i;
-        } else if let Ok((i, ())) = ignorable::<E>(input) {
-            input = i;
-        } else if let Ok((i, ())) = close_brace::<E>(input) {
-            input = i;
-            break;
-        } else if input.is_empty() {
-            return Err(E::from_context(input, "expected '}' found EOF").into_err());
-        } else {
-            return Err(E::from_context(input, "no parsers matched in block").into_err());
+            continue;
+        }
+        match block(input) {
+            Ok((i, parsed)) => {
+                blocks.push(parsed);
+                input = i;
+            }
+            // like `many1`, only an error if nothing was parsed
+            Err(nom::Err::Error(_)) if !(props.is_empty() && blocks.is_empty()) => {
+                let mut vmf = Vmf::new(blocks);
+                vmf.inner.props = props;
+                return Ok((input, vmf));
+            }
+            Err(e) => return Err(e),
         }
     }
+}
 
-    Ok((input, Block { name: name.into(), props, blocks }))
+/// Characters valid in a block name by default, the same as [`identifier`].
+pub(crate) fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// One step of parsing the inside of a block, see [`body_item`].
+enum BodyItem<P, B> {
+    Prop(P),
+    Block(B),
+    Ignored,
+    Close,
+}
+
+/// Parses the next thing inside of a block, trying in order: end of input, comments and whitespace,
+/// a property, a sub block, then the closing brace.
+/// Comments come before properties so a commented out property is never parsed as one.
+fn body_item<'a, P, B, E>(
+    input: &'a str,
+    mut prop: impl FnMut(&'a str) -> IResult<&'a str, P, E>,
+    mut block: impl FnMut(&'a str) -> IResult<&'a str, B, E>,
+) -> IResult<&'a str, BodyItem<P, B>, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    if input.is_empty() {
+        return Err(E::from_context(input, "expected '}' found EOF").into_err());
+    }
+    if let Ok((i, ())) = ignorable::<E>(input) {
+        return Ok((i, BodyItem::Ignored));
+    }
+    if let Ok((i, p)) = prop(input) {
+        return Ok((i, BodyItem::Prop(p)));
+    }
+    if let Ok((i, b)) = block(input) {
+        return Ok((i, BodyItem::Block(b)));
+    }
+    if let Ok((i, ())) = close_brace::<E>(input) {
+        return Ok((i, BodyItem::Close));
+    }
+    Err(E::from_context(input, "no parsers matched in block").into_err())
 }
 
 // Parses a [`Property`] value in the form `\s"TEXT"\s"TEXT"\s`. Where `\s` zero or more whitespace according to [`multispace0`].
@@ -76,14 +419,39 @@ where
     O: From<&'a str>,
     E: ParseError<&'a str> + ContextError<&'a str>,
 {
-    // let input: &'a str = input.as_ref();
+    property_with_quote('"')(input)
+}
+
+/// Parses a [`Property`] like [`property`], quoted by `quote` instead of `"`.
+pub fn property_with_quote<'a, O, E>(
+    quote: char,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Property<O, O>, E>
+where
+    O: From<&'a str>,
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    property_impl(quote, false)
+}
+
+/// Parses a [`Property`] quoted by `quote`, with [`string_escaped_with_quote`] if `escaped`.
+fn property_impl<'a, O, E>(
+    quote: char,
+    escaped: bool,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Property<O, O>, E>
+where
+    O: From<&'a str>,
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    let string = move |input| match escaped {
+        true => string_escaped_with_quote(quote)(input),
+        false => string_with_quote(quote)(input),
+    };
     context(
         "property error",
         map(ignore_whitespace(separated_pair(string, multispace0, string)), |(key, value)| {
             Property { key: key.into(), value: value.into() }
         }),
-    )(input)
-    // )(input.as_ref())
+    )
 }
 
 /// Parses a string in the form: `"TEXT"`, TEXT is any character other than a double quote. Consumes double quotes, does not consume whitespace.
@@ -95,8 +463,39 @@ where
     context("string error", surrounded_by(char('"'), take_until("\""), char('"')))(input)
 }
 
+/// Parses a string like [`string`], quoted by `quote` instead of `"`.
+pub fn string_with_quote<'a, E>(quote: char) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    context("string error", surrounded_by(char(quote), take_till(move |c| c == quote), char(quote)))
+}
+
+/// Parses a string like [`string`], but a backslash escapes the next character so `\"` does
+/// not end the string. Returns the text between the quotes as is, without unescaping.
+/// Slower than [`string`], which most vmf files never need. See [`ParseConfig::escaped_quotes`].
+pub fn string_escaped<'a, E>(input: &'a str) -> IResult<&'a str, &'a str, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    string_escaped_with_quote('"')(input)
+}
+
+/// Parses a string like [`string_escaped`], quoted by `quote` instead of `"`.
+pub fn string_escaped_with_quote<'a, E>(
+    quote: char,
+) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    // `escaped` errors on empty strings
+    let unescaped = take_while1(move |c| c != '\\' && c != quote);
+    let text = recognize(many0_count(alt((unescaped, recognize(pair(char('\\'), anychar))))));
+    context("string error", surrounded_by(char(quote), text, char(quote)))
+}
+
 /// [`comment`] or [`multispace1`]
-fn ignorable<'a, E>(input: &'a str) -> IResult<&'a str, (), E>
+pub(crate) fn ignorable<'a, E>(input: &'a str) -> IResult<&'a str, (), E>
 where
     E: ParseError<&'a str> + ContextError<&'a str>,
 {
@@ -113,15 +512,15 @@ where
 }
 
 /// "\s{\s"
-fn open_brace<'a, E>(input: &'a str) -> IResult<&'a str, (), E>
+pub(crate) fn open_brace<'a, E>(input: &'a str) -> IResult<&'a str, (), E>
 where
     E: ParseError<&'a str> + ContextError<&'a str>,
 {
     context("missing '{'", value((), ignore_whitespace(char('{'))))(input)
 }
 
-/// "\s{\s"
-fn close_brace<'a, E>(input: &'a str) -> IResult<&'a str, (), E>
+/// "\s}\s"
+pub(crate) fn close_brace<'a, E>(input: &'a str) -> IResult<&'a str, (), E>
 where
     E: ParseError<&'a str> + ContextError<&'a str>,
 {
@@ -177,7 +576,7 @@ where
 
 /// The same as [`is_not`] but doesn't fail if no chars before a matched one
 /// because thats kinda dumb.
-const fn is_not_no_fail<T, Input, Error: ParseError<Input>>(
+pub(crate) const fn is_not_no_fail<T, Input, Error: ParseError<Input>>(
     arr: T,
 ) -> impl Fn(Input) -> IResult<Input, Input, Error>
 where
@@ -295,4 +694,445 @@ ClassName_1 {
         assert_eq!(INPUT, output_no_white);
         assert_eq!(output, output_no_white);
     }
+
+    #[test]
+    fn bytes_lossy() {
+        let input = b"// bad byte \xFF in a comment\nClassName_1{\"Property_1\" \"Value_1\"}";
+        let truth = crate::parse::<&str, ()>("ClassName_1{\"Property_1\" \"Value_1\"}").unwrap();
+
+        assert!(crate::parse_bytes::<&str, ()>(input).is_err());
+        let output = crate::parse_bytes_lossy::<String, ()>(input).unwrap();
+        assert_eq!(output.to_string(), truth.to_string());
+
+        let valid = b"// no bad bytes\nClassName_1{\"Property_1\" \"Value_1\"}";
+        let output = crate::parse_bytes::<&str, VerboseError<_>>(valid).unwrap();
+        assert_eq!(output, truth);
+    }
+
+    #[test]
+    fn partial() {
+        let (vmf, rest) =
+            crate::parse_partial::<&str, VerboseError<_>>("a{\"k\" \"v\"}\nb{}\nc{ \"k\" \"v")
+                .unwrap();
+        assert_eq!(vmf, crate::parse::<&str, ()>("a{\"k\" \"v\"}b{}").unwrap());
+        assert_eq!(rest, "c{ \"k\" \"v");
+
+        let (vmf, rest) = crate::parse_partial::<&str, ()>(INPUT).unwrap();
+        assert_eq!(vmf.to_string(), INPUT);
+        assert_eq!(rest, "");
+
+        let (vmf, rest) = crate::parse_partial::<&str, ()>("incomplete{").unwrap();
+        assert!(vmf.blocks.is_empty());
+        assert_eq!(rest, "incomplete{");
+    }
+
+    #[test]
+    fn utf16() {
+        let utf16_le = |s: &str| {
+            let mut bytes = vec![0xFF, 0xFE];
+            bytes.extend(s.encode_utf16().flat_map(u16::to_le_bytes));
+            bytes
+        };
+        let truth = crate::parse::<String, ()>(INPUT).unwrap();
+
+        let output = crate::parse_utf16::<()>(&utf16_le(INPUT)).unwrap();
+        assert_eq!(output, truth);
+
+        let mut utf16_be = vec![0xFE, 0xFF];
+        utf16_be.extend(INPUT.encode_utf16().flat_map(u16::to_be_bytes));
+        assert_eq!(crate::parse_utf16::<()>(&utf16_be).unwrap(), truth);
+
+        let mut odd = utf16_le(INPUT);
+        odd.push(0);
+        assert!(crate::parse_utf16::<()>(&odd).is_err());
+        // lone surrogate
+        assert!(crate::parse_utf16::<()>(&[0xFF, 0xFE, 0x00, 0xD8]).is_err());
+    }
+
+    #[test]
+    fn parse_into() {
+        let mut vmf = Vmf::default();
+
+        crate::parse_into::<&str, ()>(INPUT, &mut vmf).unwrap();
+        assert_eq!(vmf, crate::parse::<&str, ()>(INPUT).unwrap());
+        let capacity = vmf.blocks.capacity();
+
+        let input = "a{} b{} c{}";
+        crate::parse_into::<&str, ()>(input, &mut vmf).unwrap();
+        assert_eq!(vmf, crate::parse::<&str, ()>(input).unwrap());
+        assert!(vmf.blocks.capacity() >= capacity);
+
+        assert!(crate::parse_into::<&str, ()>("", &mut vmf).is_err());
+        assert!(vmf.blocks.is_empty());
+    }
+
+    #[test]
+    fn interned() {
+        use std::collections::HashMap;
+        use std::rc::Rc;
+
+        let mut strings: HashMap<String, Rc<str>> = HashMap::new();
+        let mut misses = 0;
+        let intern = |s: &str| {
+            strings
+                .entry(s.to_string())
+                .or_insert_with(|| {
+                    misses += 1;
+                    Rc::from(s)
+                })
+                .clone()
+        };
+
+        let input = r#"entity { "classname" "light" } entity { "classname" "light_spot" }"#;
+        let vmf = crate::parse_interned::<Rc<str>, (), _>(input, intern).unwrap();
+        // root, entity, classname, light, light_spot
+        assert_eq!(misses, 5);
+
+        let (a, b) = (&vmf.blocks[0], &vmf.blocks[1]);
+        assert!(Rc::ptr_eq(&a.name, &b.name));
+        assert!(Rc::ptr_eq(&a.props[0].key, &b.props[0].key));
+        assert!(!Rc::ptr_eq(&a.props[0].value, &b.props[0].value));
+        assert_eq!(vmf.to_string(), crate::parse::<&str, ()>(input).unwrap().to_string());
+    }
+
+    #[test]
+    fn counted() {
+        let (vmf, count) = crate::parse_counted::<&str, ()>(INPUT).unwrap();
+        assert_eq!(vmf.to_string(), INPUT);
+        assert_eq!(count, INPUT.len());
+
+        let input = "\n a{}\n\tb{ \"k\" \"v\" }\n\n// trailing comment\n";
+        let (_, count) = crate::parse_counted::<&str, ()>(input).unwrap();
+        assert_eq!(count, input.trim_end_matches("// trailing comment\n").len());
+
+        let input = "a{} b{} \"not a block\"";
+        let (vmf, count) = crate::parse_counted::<&str, ()>(input).unwrap();
+        assert_eq!(vmf.blocks.len(), 2);
+        assert_eq!(&input[count..], "\"not a block\"");
+    }
+
+    #[test]
+    fn commented_out_property() {
+        let input = "entity\n{\n\t// \"fake\" \"prop\"\n\t\"classname\" \"light\" // \"also\" \"fake\"\n\t//\"solid\" {\n}";
+        let vmf = crate::parse::<&str, ()>(input).unwrap();
+        assert_eq!(vmf.blocks[0].props, vec![Property::new("classname", "light")]);
+        assert!(vmf.blocks[0].blocks.is_empty());
+
+        let ordered = crate::parse_ordered::<&str, ()>(input).unwrap();
+        assert_eq!(ordered.blocks[0].items.len(), 1);
+    }
+
+    #[test]
+    fn partial_ast() {
+        let input =
+            "world\n{\n\t\"id\" \"1\"\n}\nentity\n{\n\t\"id\" \"2\"\n}\nentity\n{\n\t\"id\" 3\n}\n";
+        let (vmf, report) = crate::parse_partial_ast(input);
+        assert_eq!(vmf.blocks.len(), 2);
+        assert_eq!(vmf.blocks[1].props[0], Property::new("id".to_string(), "2".to_string()));
+
+        let report = report.unwrap();
+        assert_eq!((report.line, report.column), (11, 2));
+        assert_eq!(&input[report.offset..report.offset + 3], "\"id");
+        assert_eq!(report.message, "no parsers matched in block");
+
+        let (vmf, report) = crate::parse_partial_ast("a{}\n// trailing comment\n");
+        assert_eq!((vmf.blocks.len(), report), (1, None));
+
+        let (vmf, report) = crate::parse_partial_ast("   ");
+        assert!(vmf.blocks.is_empty());
+        assert_eq!(report.unwrap().message, "expected block found EOF");
+    }
+
+    #[test]
+    fn whitespace_values() {
+        let input = "entity\n{\n\t\" \" \" \"\n\t\"empty\" \"\"\n\t\"tabs\" \"\t\t\"\n\t\"padded\" \"  a  \"\n}";
+        let vmf = crate::parse::<&str, ()>(input).unwrap();
+        assert_eq!(
+            vmf.blocks[0].props,
+            vec![
+                Property::new(" ", " "),
+                Property::new("empty", ""),
+                Property::new("tabs", "\t\t"),
+                Property::new("padded", "  a  "),
+            ]
+        );
+        assert_eq!(vmf.to_string(), input);
+    }
+
+    #[test]
+    fn binary_input() {
+        let input = "\u{0}\u{1}\u{7f}garbage{}\u{ff}";
+        let err = crate::parse::<&str, VerboseError<&str>>(input).unwrap_err();
+        assert_eq!(err.errors[1], (input, VerboseErrorKind::Context("not a VMF file")));
+
+        let err = crate::parse::<&str, VerboseError<&str>>("\n\t{}").unwrap_err();
+        assert_eq!(err.errors[1], ("{}", VerboseErrorKind::Context("not a VMF file")));
+
+        // comments and names are fine
+        assert!(crate::parse::<&str, ()>("// comment\n_block{}").is_ok());
+    }
+
+    #[test]
+    fn eof_in_block() {
+        let eof = VerboseErrorKind::Context("expected '}' found EOF");
+        for input in ["a{", "a{\n\t", "a{ \"k\" \"v\"", "a{ // comment", "a{ b{} ", "a{ b{ } \n"] {
+            let err = block::<&str, VerboseError<&str>>(input).unwrap_err().unwrap_error();
+            assert_eq!(err.errors[1], ("", eof.clone()), "{input:?}");
+            let err = ordered_block::<&str, VerboseError<&str>>(input).unwrap_err().unwrap_error();
+            assert_eq!(err.errors[1], ("", eof.clone()), "{input:?}");
+        }
+
+        // sub block ends early, so the outer block cannot continue
+        let err = block::<&str, VerboseError<&str>>("a{ b{").unwrap_err().unwrap_error();
+        assert_eq!(err.errors[1], ("b{", VerboseErrorKind::Context("no parsers matched in block")));
+        assert!(crate::parse::<&str, ()>("a{ b{").is_err());
+
+        assert!(block::<&str, ()>("a{ b{} }").is_ok());
+    }
+
+    #[test]
+    fn ident_pred() {
+        let dotted = |c: char| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-');
+        let input = "sub.block-1 { \"key\" \"value\" child-2 {} }";
+        let (_, parsed) = block_with_ident_pred::<&str, ()>(dotted)(input).unwrap();
+        assert_eq!(parsed.name, "sub.block-1");
+        assert_eq!(parsed.props, vec![Property::new("key", "value")]);
+        assert_eq!(parsed.blocks[0].name, "child-2");
+
+        assert!(block::<&str, ()>(input).is_err());
+        assert!(block_with_ident_pred::<&str, ()>(char::is_alphabetic)("a_b {}").is_err());
+    }
+
+    #[test]
+    fn value_hook() {
+        use std::borrow::Cow;
+
+        let input = "entity\n{\n\t\"$key\" \"$name_1\"\n\t\"message\" \"hello $name\"\n}";
+        let vmf = crate::parse_with_value_hook::<()>(input, |value| {
+            if value.contains("$name") {
+                Cow::Owned(value.replace("$name", "world"))
+            } else {
+                Cow::Borrowed(value)
+            }
+        })
+        .unwrap();
+        assert_eq!(vmf.blocks[0].props[0], Property::new("$key", "world_1"));
+        assert_eq!(vmf.blocks[0].props[1], Property::new("message", "hello world"));
+
+        // no-op
+        let vmf = crate::parse_with_value_hook::<()>(input, Cow::Borrowed).unwrap();
+        assert_eq!(vmf.to_string(), input);
+    }
+
+    #[test]
+    fn config() {
+        let config = ParseConfig::default()
+            .with_quote_char('\'')
+            .with_ident_pred(|c| c.is_ascii_alphabetic() || c == '-')
+            .with_strip_bom(true)
+            .with_require_complete(true)
+            .with_join_line_continuations(true)
+            .with_normalize_newlines(true);
+        let raw = "\u{feff}sub-block\n{\n\t'key' 'first \\\nsecond'\n\tchild {}\n}\n// trailing comment\n";
+        let input = config.preprocess(raw);
+        let vmf = crate::parse_with_config::<&str, ()>(&input, &config).unwrap();
+        assert_eq!(vmf.blocks[0].name, "sub-block");
+        assert_eq!(vmf.blocks[0].props, vec![Property::new("key", "first second")]);
+        assert_eq!(vmf.blocks[0].blocks[0].name, "child");
+
+        // trailing garbage
+        let err = crate::parse_with_config::<&str, VerboseError<&str>>("a{} b{ 'k' }", &config)
+            .unwrap_err();
+        assert_eq!(
+            err.errors[1],
+            ("'k' }", VerboseErrorKind::Context("no parsers matched in block"))
+        );
+        // ignored by default
+        assert!(crate::parse::<&str, ()>("a{} b{ \"k\" }").is_ok());
+
+        // default is the same as parse
+        assert!(crate::parse::<&str, ()>(raw).is_err());
+        let default = ParseConfig::default();
+        assert_eq!(default.preprocess(INPUT), INPUT);
+        assert_eq!(
+            crate::parse_with_config::<&str, ()>(INPUT, &default),
+            crate::parse::<&str, ()>(INPUT)
+        );
+    }
+
+    #[test]
+    fn config_newlines() {
+        let config =
+            ParseConfig::default().with_normalize_newlines(true).with_join_line_continuations(true);
+        let input = config.preprocess("a\r{\r\n\"k\" \"one \\\rtwo\"\r}");
+        assert_eq!(input, "a\n{\n\"k\" \"one two\"\n}");
+        let vmf = crate::parse_with_config::<&str, ()>(&input, &config).unwrap();
+        assert_eq!(vmf.blocks[0].props[0].value, "one two");
+
+        // applied by the owned entry point
+        let vmf =
+            crate::parse_with_config_owned::<String>("a\r{\r\n\"k\" \"one \\\rtwo\"\r}", &config)
+                .unwrap();
+        assert_eq!(vmf.blocks[0].props[0].value, "one two");
+        let err = crate::parse_with_config_owned::<String>("a\r{\r\"k\"\r}", &config).unwrap_err();
+        assert_eq!((err.line, err.column), (3, 1));
+    }
+
+    #[test]
+    fn root_properties() {
+        let input =
+            "// header\n\"version\" \"1\"\n\"name\" \"test\"\nworld\n{\n}\n\"after\" \"2\"\n";
+        assert!(crate::parse::<&str, ()>(input).is_err(), "strict mode requires blocks");
+
+        let config = ParseConfig::default().with_root_properties(true);
+        let vmf = crate::parse_with_config::<&str, ()>(input, &config).unwrap();
+        assert_eq!(
+            vmf.props,
+            vec![
+                Property::new("version", "1"),
+                Property::new("name", "test"),
+                Property::new("after", "2")
+            ]
+        );
+        assert_eq!(vmf.blocks, vec![Block::new("world", vec![], vec![])]);
+
+        let only_props = crate::parse_with_config::<&str, ()>("\"k\" \"v\"", &config).unwrap();
+        assert_eq!(only_props.props, vec![Property::new("k", "v")]);
+        assert!(crate::parse_with_config::<&str, ()>("\"k\"", &config).is_err());
+    }
+
+    #[test]
+    fn string_matrix() {
+        let config = ParseConfig::default().with_escaped_quotes(true);
+        // input, `string` output, `string_escaped` output
+        let matrix: [(&str, Option<&str>, Option<&str>); 9] = [
+            (r#""""#, Some(""), Some("")),
+            (r#""plain text""#, Some("plain text"), Some("plain text")),
+            (r#""a\"b""#, Some(r"a\"), Some(r#"a\"b"#)),
+            (r#""\"""#, Some(r"\"), Some(r#"\""#)),
+            (
+                r#""materials\dev\floor""#,
+                Some(r"materials\dev\floor"),
+                Some(r"materials\dev\floor"),
+            ),
+            (r#""C:\maps\""#, Some(r"C:\maps\"), None),
+            (r#""a\\""#, Some(r"a\\"), Some(r"a\\")),
+            (r#""say \"hi\" \\o/""#, Some(r"say \"), Some(r#"say \"hi\" \\o/"#)),
+            (r#""unterminated"#, None, None),
+        ];
+        for (input, simple, escaped) in matrix {
+            assert_eq!(string::<()>(input).ok().map(|(_, s)| s), simple, "string {input}");
+            assert_eq!(
+                string_escaped::<()>(input).ok().map(|(_, s)| s),
+                escaped,
+                "escaped {input}"
+            );
+
+            let block = format!("block {{ \"key\" {input} }}");
+            let vmf = crate::parse_with_config::<&str, ()>(&block, &config);
+            assert_eq!(
+                vmf.ok().map(|vmf| vmf.blocks[0].props[0].value),
+                escaped,
+                "escaped_quotes {input}"
+            );
+        }
+    }
+
+    #[test]
+    fn index_top_level() {
+        let input = r#"// "quoted" comment with a {
+versioninfo
+{
+	"editorversion" "400"
+}
+world
+{
+	"id" "1"
+	"comment" "{ not a brace }"
+	solid
+	{
+		"id" "2"
+	}
+}
+entity { "id" "3" }
+entity { "id" "4" }
+broken {"#;
+        let index = crate::index_top_level(input);
+        assert_eq!(index.len(), 3);
+        assert_eq!(index["entity"].len(), 2);
+        assert!(!index.contains_key("broken"));
+
+        let world = index["world"][0].clone();
+        let vmf = crate::parse::<&str, ()>(&input[world]).unwrap();
+        assert_eq!(vmf.blocks.len(), 1);
+        assert_eq!(vmf.blocks[0].name, "world");
+        assert_eq!(vmf.blocks[0].props[1], Property::new("comment", "{ not a brace }"));
+        assert_eq!(vmf.blocks[0].blocks[0].props[0], Property::new("id", "2"));
+
+        let entity = &input[index["entity"][1].clone()];
+        assert_eq!(entity, r#"entity { "id" "4" }"#);
+    }
+
+    #[test]
+    fn config_lowercase_names() {
+        let config = ParseConfig::default().with_lowercase_names(true);
+        let input = config.preprocess("World { SOLID { \"ID\" \"Value\" } }");
+        let vmf = crate::parse_with_config::<&str, ()>(&input, &config).unwrap();
+        let solid = vmf.find_block("world").unwrap().find_block("solid").unwrap();
+        assert_eq!(solid.props, vec![Property::new("ID", "Value")], "properties are unchanged");
+
+        let default = ParseConfig::default();
+        let input = default.preprocess("SOLID {}");
+        assert!(matches!(input, Cow::Borrowed(_)));
+        let vmf = crate::parse_with_config::<&str, ()>(&input, &default).unwrap();
+        assert!(vmf.find_block("solid").is_none());
+
+        // applied by the owned entry point
+        let vmf = crate::parse_with_config_owned::<String>("SOLID {}", &config).unwrap();
+        assert!(vmf.find_block("solid").is_some());
+    }
+
+    #[test]
+    fn reader_and_file() {
+        let input = "world\n{\n\t\"id\" \"1\"\n}";
+        let vmf = crate::parse_reader::<_, String>(input.as_bytes()).unwrap().unwrap();
+        assert_eq!(vmf.to_string(), input);
+        assert!(crate::parse_reader::<_, String>(&b"world{\xFF}"[..]).is_err(), "io error");
+        let err =
+            crate::parse_reader::<_, String>(&b"world{\n\t\"k\"\n}"[..]).unwrap().unwrap_err();
+        assert_eq!((err.line, err.column), (2, 2));
+        assert_eq!(err.message, "no parsers matched in block");
+
+        let path = std::env::temp_dir().join(format!("vmf_parser_nom_{}.vmf", std::process::id()));
+        std::fs::write(&path, input).unwrap();
+        let from_file = crate::parse_file::<_, String>(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(from_file.unwrap().unwrap(), vmf);
+
+        let missing = crate::parse_file::<_, String>(&path).unwrap_err();
+        assert_eq!(missing.kind(), std::io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn config_unescape_control() {
+        let input = r#"// C:\temp\new
+entity { "message" "tab\there" "path" "C:\\new" "key\n" "plain" }"#;
+        let config = ParseConfig::default().with_unescape_control(true);
+        let vmf = crate::parse_with_config_owned::<String>(input, &config).unwrap();
+        let props = &vmf.blocks[0].props;
+        assert_eq!(props[0], Property::new("message".to_string(), "tab\there".to_string()));
+        assert_eq!(props[1].value, "C:\\new");
+        assert_eq!(props[2].key, "key\n");
+
+        // borrowing any unchanged strings
+        let parsed = crate::parse_with_config::<&str, ()>(input, &config).unwrap();
+        let vmf = config.postprocess(parsed);
+        assert_eq!(vmf.blocks[0].props[1].value, "C:\\new");
+        assert!(matches!(vmf.blocks[0].props[2].value, Cow::Borrowed("plain")));
+
+        // off by default
+        let parsed = crate::parse::<&str, ()>(input).unwrap();
+        let vmf = ParseConfig::default().postprocess(parsed);
+        assert_eq!(vmf.blocks[0].props[0].value, r"tab\there");
+    }
 }
@@ -0,0 +1,228 @@
+//! Lenient helpers that rewrite input before it is parsed.
+//!
+//! The output is borrowed when nothing needed to change, so the result can be passed
+//! straight to [`parse()`](crate::parse).
+
+use std::borrow::Cow;
+
+/// Joins lines ending in a `\` inside of quoted strings, removing the backslash and the
+/// line ending. For hand written files that continue long values on the next line.
+/// Comments and anything else outside of quotes are left as is.
+///
+/// Without this, [`parse()`](crate::parse) treats the `\` and the line ending as
+/// part of the value.
+///
+/// # Examples
+///
+/// ```rust
+/// use vmf_parser_nom::preprocess::join_line_continuations;
+///
+/// let input = "block{\"key\" \"a long \\\nvalue\"}";
+/// assert_eq!(join_line_continuations(input), "block{\"key\" \"a long value\"}");
+/// ```
+pub fn join_line_continuations(input: &str) -> Cow<'_, str> {
+    join_line_continuations_with_quote(input, '"')
+}
+
+/// Joins continued lines like [`join_line_continuations`], with strings quoted by `quote` instead of `"`.
+pub fn join_line_continuations_with_quote(input: &str, quote: char) -> Cow<'_, str> {
+    if !input.contains("\\\n") && !input.contains("\\\r\n") {
+        return Cow::Borrowed(input);
+    }
+
+    let mut joined: Option<String> = None;
+    let (mut in_quote, mut in_comment) = (false, false);
+    let mut chars = input.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if in_comment {
+            in_comment = c != '\n';
+        } else if in_quote {
+            let rest = &input[i + c.len_utf8()..];
+            let line_ending = if c != '\\' {
+                0
+            } else if rest.starts_with('\n') {
+                1
+            } else if rest.starts_with("\r\n") {
+                2
+            } else {
+                0
+            };
+            if line_ending > 0 {
+                joined.get_or_insert_with(|| input[..i].to_string());
+                chars.nth(line_ending - 1);
+                continue;
+            }
+            in_quote = c != quote;
+        } else if c == quote {
+            in_quote = true;
+        } else if c == '/' && chars.peek().map(|&(_, next)| next) == Some('/') {
+            in_comment = true;
+        }
+        if let Some(joined) = joined.as_mut() {
+            joined.push(c);
+        }
+    }
+    joined.map_or(Cow::Borrowed(input), Cow::Owned)
+}
+
+/// Converts `\r\n` and lone `\r` line endings into `\n`.
+/// Line and column numbers, like in [`ParseErrorReport`](crate::nom_helpers::ParseErrorReport),
+/// only count `\n`, so this makes them the same for any line endings.
+pub fn normalize_newlines(input: &str) -> Cow<'_, str> {
+    if !input.contains('\r') {
+        return Cow::Borrowed(input);
+    }
+    Cow::Owned(input.replace("\r\n", "\n").replace('\r', "\n"))
+}
+
+/// ASCII lowercases every block name, so names can be compared exactly.
+/// Only changes characters outside of `"` quoted strings and comments, keeping byte offsets the same.
+///
+/// # Examples
+///
+/// ```rust
+/// use vmf_parser_nom::preprocess::lowercase_block_names;
+///
+/// let input = "World{\"Key\" \"Value\" SOLID{}} // Comment";
+/// assert_eq!(lowercase_block_names(input), "world{\"Key\" \"Value\" solid{}} // Comment");
+/// ```
+pub fn lowercase_block_names(input: &str) -> Cow<'_, str> {
+    lowercase_block_names_with_quote(input, '"')
+}
+
+/// Lowercases block names like [`lowercase_block_names`], with strings quoted by `quote` instead of `"`.
+pub fn lowercase_block_names_with_quote(input: &str, quote: char) -> Cow<'_, str> {
+    let mut output: Option<String> = None;
+    let (mut in_quote, mut in_comment) = (false, false);
+    let mut chars = input.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if in_comment {
+            in_comment = c != '\n';
+        } else if in_quote {
+            in_quote = c != quote;
+        } else if c == quote {
+            in_quote = true;
+        } else if c == '/' && chars.peek().map(|&(_, next)| next) == Some('/') {
+            in_comment = true;
+        } else if c.is_ascii_uppercase() {
+            output.get_or_insert_with(|| input.to_string())[i..i + 1].make_ascii_lowercase();
+        }
+    }
+    output.map_or(Cow::Borrowed(input), Cow::Owned)
+}
+
+/// Unescapes `\t`, `\n`, `\r`, and `\\` in a single key or value into tabs, newlines,
+/// carriage returns, and backslashes. Any other backslashes are kept as is.
+/// The opposite of [`DisplayOptions::escape_control`](crate::ast::DisplayOptions::escape_control).
+///
+/// Not for whole files, as comments and block names would change too. See
+/// [`ParseConfig::unescape_control`](crate::parsers::ParseConfig::unescape_control) to
+/// unescape while parsing.
+pub fn unescape_control(input: &str) -> Cow<'_, str> {
+    if !input.contains('\\') {
+        return Cow::Borrowed(input);
+    }
+
+    let mut unescaped = String::with_capacity(input.len());
+    let mut chars = input.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => unescaped.push('\t'),
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some('\\') => unescaped.push('\\'),
+            Some(other) => {
+                unescaped.push('\\');
+                unescaped.push(other);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+    Cow::Owned(unescaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::Property;
+
+    #[test]
+    fn line_continuations() {
+        let input =
+            "entity\n{\n\t\"message\" \"first \\\nsecond \\\r\nthird\"\n\t\"path\" \"C:\\\\\"\n}";
+
+        // strict, backslash is kept literally
+        let strict = crate::parse::<&str, ()>(input).unwrap();
+        assert_eq!(
+            strict.blocks[0].props[0],
+            Property::new("message", "first \\\nsecond \\\r\nthird")
+        );
+
+        // lenient
+        let joined = join_line_continuations(input);
+        assert!(matches!(joined, Cow::Owned(_)));
+        let lenient = crate::parse::<&str, ()>(&joined).unwrap();
+        assert_eq!(lenient.blocks[0].props[0], Property::new("message", "first second third"));
+        assert_eq!(lenient.blocks[0].props[1], Property::new("path", "C:\\\\"));
+
+        assert!(matches!(join_line_continuations("no\\continuations"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn line_continuations_outside_quotes() {
+        // a comment ending in a backslash does not swallow the next line
+        let input = "// C:\\maps\\\nworld\n{\n\t\"k\" \"a \\\r\nb\"\n}\\\n";
+        let joined = join_line_continuations(input);
+        assert_eq!(joined, "// C:\\maps\\\nworld\n{\n\t\"k\" \"a b\"\n}\\\n");
+        let vmf = crate::parse::<&str, ()>(&joined).unwrap();
+        assert_eq!(vmf.blocks[0].props[0], Property::new("k", "a b"));
+
+        let input = "// only a comment \\\nworld{}";
+        assert!(matches!(join_line_continuations(input), Cow::Borrowed(_)));
+        assert_eq!(join_line_continuations_with_quote("a{'k' 'x\\\ny'}", '\''), "a{'k' 'xy'}");
+    }
+
+    #[test]
+    fn unescape() {
+        assert_eq!(unescape_control(r"a\tb\nc\rd\\e"), "a\tb\nc\rd\\e");
+        assert_eq!(unescape_control(r"\x \"), r"\x \");
+        assert!(matches!(unescape_control("nothing"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn newlines() {
+        assert_eq!(normalize_newlines("a\r\nb\rc\nd\r\r\n"), "a\nb\nc\nd\n\n");
+        assert!(matches!(normalize_newlines("a\nb"), Cow::Borrowed(_)));
+
+        // same error location for any line endings
+        let lf = "world\n{\n}\nentity\n{\n\t\"id\"\n}";
+        let reports: Vec<_> = [lf.to_string(), lf.replace('\n', "\r\n"), lf.replace('\n', "\r")]
+            .iter()
+            .map(|input| {
+                let input = normalize_newlines(input);
+                let (_, report) = crate::parse_partial_ast(&input);
+                report.unwrap()
+            })
+            .collect();
+        assert_eq!((reports[0].line, reports[0].column), (6, 2));
+        assert_eq!(reports[0], reports[1]);
+        assert_eq!(reports[0], reports[2]);
+    }
+
+    #[test]
+    fn lowercase_names() {
+        let input =
+            "// World\nWORLD\n{\n\t\"ClassName\" \"WorldSpawn\"\n\tSolid_1 { \"k\" \"V\" }\n}";
+        let lowered = lowercase_block_names(input);
+        assert_eq!(
+            lowered,
+            "// World\nworld\n{\n\t\"ClassName\" \"WorldSpawn\"\n\tsolid_1 { \"k\" \"V\" }\n}"
+        );
+        assert!(matches!(lowercase_block_names("world{\"A\" \"B\"}"), Cow::Borrowed(_)));
+        assert_eq!(lowercase_block_names_with_quote("A{'B' 'C'}", '\''), "a{'B' 'C'}");
+    }
+}